@@ -1,15 +1,16 @@
 use hecs::{ Entity, World };
 
 use crate::{
-    ability::Ability,
-    render::{ Sprite, Costume },
+    ability::{ AbilityDef, Ability },
+    render::{ Sprite, Costume, Visibility },
     transform::{ Transform, LocalPosition },
     input::FollowLookDirection,
     physics::Collider,
     math::{ Rot2, vec2 }, platform::{Time, Connection, Socket}, network::Packet,
+    timescale::TimeScale,
 };
 
-use super::{Shield, Cooldown};
+use super::{Shield, Cooldown, stats};
 
 /// Component for a bubble shield.
 pub struct BubbleShield {
@@ -17,41 +18,85 @@ pub struct BubbleShield {
     pub radius: f32,
 }
 
-pub fn instantiate(world: &mut World, owner: Entity, binding: usize) -> Entity {
-    world.spawn((
-        Ability {
-            owner,
-            binding,
-            active: false,
-        },
-        Sprite::new(Costume::BubbleShield {
-            position: Default::default(),
-            radius: Default::default(),
-        }),
-        BubbleShield { radius: 50.0 },
-        Shield(owner),
-        Cooldown(0.0),
-        Transform::default(),
-        Collider::circle(50.0),
-        LocalPosition(vec2!(0.0, 0.0)),
-    ))
+/// Bubble shield ability, see [AbilityDef].
+pub struct BubbleShieldAbility;
+
+impl AbilityDef for BubbleShieldAbility {
+    fn id(&self) -> &'static str {
+        "bubble-shield"
+    }
+
+    fn instantiate(&self, world: &mut World, owner: Entity, binding: usize) -> Entity {
+        let stats = &stats().bubble_shield;
+        world.spawn((
+            Ability {
+                owner,
+                binding,
+                active: false,
+            },
+            Sprite::new(Costume::BubbleShield {
+                position: Default::default(),
+                radius: Default::default(),
+            }),
+            BubbleShield { radius: stats.radius },
+            Shield(owner),
+            Cooldown(0.0),
+            Transform::default(),
+            Collider::circle(stats.radius),
+            LocalPosition(vec2!(0.0, 0.0)),
+        ))
+    }
+
+    fn update(&self, world: &mut World, socket: &Socket, time: &Time) {
+        shrink(world, socket, time);
+    }
+
+    fn effects(&self, world: &mut World, socket: &Socket, time: &Time) {
+        shrink(world, socket, time);
+    }
+
+    fn animate(&self, world: &mut World) {
+        for (_, (transform, ability, shield, cooldown, sprite)) in world.query_mut::<(
+            &Transform, &Ability, &BubbleShield, &Cooldown, &mut Sprite
+        )>() {
+            let Costume::BubbleShield { position, radius } = &mut sprite.costume else {
+                continue;
+            };
+            let target = transform.translation;
+            let delta = target - *position;
+
+            // Damp position
+            *position += 0.9 * delta;
+            // Radius is exact
+            *radius = shield.radius.max(0.0);
+            // Visibility
+            sprite.visibility = match ability.active && cooldown.0 <= 0.0 {
+                true => Visibility::Shown,
+                false => Visibility::Hidden,
+            };
+        }
+    }
 }
 
-/// System that positions the shield
-pub fn bubble_shield_controller(world: &mut World, socket: &Socket, time: &Time) {
-    for (_, (ability, shield, cooldown, collider)) in &mut world.query::<(
-        &Ability, &mut BubbleShield, &mut Cooldown, &mut Collider
+/// Shrink active bubble shields, runs on both ends.
+fn shrink(world: &mut World, socket: &Socket, time: &Time) {
+    let stats = &stats().bubble_shield;
+    for (_, (ability, shield, cooldown, collider, scale)) in &mut world.query::<(
+        &Ability, &mut BubbleShield, &mut Cooldown, &mut Collider, Option<&TimeScale>
     )>() {
+        let scale = scale
+            .map(|s| s.0)
+            .unwrap_or(1.0);
         // Cooldown
-        cooldown.0 -= time.dt();
+        cooldown.0 -= time.dt() * scale;
         // Shrink shield
         if ability.active && cooldown.0 <= 0.0 {
-            shield.radius -= 1.5 * time.dt();
+            shield.radius -= stats.shrink * time.dt() * scale;
             *collider = Collider::circle(shield.radius);
         }
-        if shield.radius <= 15.0 {
-            shield.radius = 50.0;
-            *cooldown = Cooldown(5.0);
+        if shield.radius <= stats.min_radius {
+            shield.radius = stats.radius;
+            *cooldown = Cooldown(stats.cooldown);
             if let Ok(id) = world.get::<&Connection>(ability.owner) {
                 socket.send(*id, &Packet::CooldownStart {
                     binding: ability.binding,
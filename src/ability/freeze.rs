@@ -1,37 +1,72 @@
 use hecs::{ World, Entity, With };
 
 use crate::{
-    ability::{ Ability, Cooldown },
+    ability::{ AbilityDef, Ability, Cooldown, stats },
     platform::{Time, Socket, Connection},
-    transform::Transform,
     render::{ Sprite, Costume }, bullet::TimeToLive, network::Packet,
+    timescale::{ self, TimeScale, TimeModifier, TimeTarget },
 };
 
 /// Component that marks this entity as the push ability
-struct Freeze {
-    /// Frames left to freeze
-    frames: Option<usize>
-}
+struct Freeze;
 
-/// Component for an entity's invidual time scale.
-pub struct TimeScale(pub f32);
+/// Time freeze ability, see [AbilityDef].
+pub struct TimeFreezeAbility;
 
-pub fn instantiate(world: &mut World, owner: Entity, binding: usize) -> Entity {
-    world.spawn((
-        Ability {
-            owner,
-            binding,
-            active: false,
-        },
-        Freeze { frames: None },
-        Cooldown::default(),
-    ))
-}
+impl AbilityDef for TimeFreezeAbility {
+    fn id(&self) -> &'static str {
+        "time-freeze"
+    }
+
+    fn instantiate(&self, world: &mut World, owner: Entity, binding: usize) -> Entity {
+        world.spawn((
+            Ability {
+                owner,
+                binding,
+                active: false,
+            },
+            Freeze,
+            Cooldown::default(),
+        ))
+    }
 
-/// System that controls the almighty push
-pub fn freeze_controller(world: &mut World, time: &mut Time, socket: &Socket) {
-    const SCALE: f32 = 0.3;
-    if cfg!(client) {
+    /// System that controls the almighty push
+    fn update(&self, world: &mut World, socket: &Socket, time: &Time) {
+        type Query<'a> = With<(&'a Ability, &'a mut Cooldown, Option<&'a TimeScale>), &'a Freeze>;
+        let stats = &stats().time_freeze;
+
+        let mut add = Vec::new();
+        for (_, (ability, cooldown, scale)) in &mut world.query::<Query>() {
+            let scale = scale
+                .map(|s| s.0)
+                .unwrap_or(1.0);
+            // Cooldown
+            cooldown.0 -= time.dt() * scale;
+            // Trigger
+            if ability.active && cooldown.0 <= 0.0 {
+                *cooldown = Cooldown(stats.cooldown);
+                if let Ok(id) = world.get::<&Connection>(ability.owner) {
+                    socket.send(*id, &Packet::CooldownStart {
+                        binding: ability.binding,
+                        duration: cooldown.0,
+                    })
+                }
+                add.push(ability.owner);
+            }
+        }
+        for e in add {
+            timescale::dilate(world, socket, TimeModifier {
+                source: e,
+                factor: stats.scale,
+                time_left: stats.duration,
+                target: TimeTarget::AllExcept(e),
+            });
+            // Sprite
+            socket.broadcast(&Packet::EffectSpawn(Costume::Freeze));
+        }
+    }
+
+    fn effects(&self, world: &mut World, socket: &Socket, _time: &Time) {
         for (_, packet) in socket.packets() {
             let Packet::EffectSpawn(costume) = packet else {
                 continue;
@@ -45,40 +80,5 @@ pub fn freeze_controller(world: &mut World, time: &mut Time, socket: &Socket) {
                 TimeToLive::Frames(100)
             ));
         }
-        return;
     }
-    let mut add = Vec::new();
-    let mut remove = Vec::new();
-    for (_, (ability, cooldown, freeze)) in &mut world.query::<(&Ability, &mut Cooldown, &mut Freeze)>() {
-        // Cooldown
-        cooldown.0 -= time.dt();
-        // Trigger
-        if ability.active && cooldown.0 <= 0.0 {
-            time.scale = SCALE;
-            freeze.frames = Some(240);
-            *cooldown = Cooldown(25.0);
-            if let Ok(id) = world.get::<&Connection>(ability.owner) {
-                socket.send(*id, &Packet::CooldownStart {
-                    binding: ability.binding,
-                    duration: cooldown.0,
-                })
-            }
-            add.push(ability.owner);
-        }
-        if let Some(frames) = &mut freeze.frames {
-            *frames -= 1;
-            if *frames <= 0 {
-                time.scale = 1.0;
-                remove.push(ability.owner);
-            }
-        }
-    }
-    for e in add {
-        world.insert_one(e, TimeScale(1.0 / SCALE)).unwrap();
-        // Sprite
-        socket.broadcast(&Packet::EffectSpawn(Costume::Freeze));
-    }
-    for e in remove {
-        world.remove_one::<TimeScale>(e).unwrap();
-    }
-}
\ No newline at end of file
+}
@@ -0,0 +1,221 @@
+use hecs::{ Entity, World };
+use parry2d::shape::Ball;
+
+use crate::{
+    ability::{ AbilityDef, Ability, Cooldown, stats },
+    platform::{ Socket, Time, Connection },
+    transform::Transform,
+    render::{ Sprite, Costume, Visibility },
+    input::Input,
+    network::Packet,
+    physics::{ self, KinematicBody, Collider, QueryFilter, Layers },
+    math::Vec2,
+    timescale::TimeScale,
+};
+
+/// Component for the ODM gear ability
+#[derive(Debug, Default)]
+pub struct Grapple {
+    /// Where the hook is attached, if it is
+    hook: Option<Hook>,
+    /// Ability active last frame?
+    was_active: bool,
+}
+
+/// A hook stuck in level geometry.
+#[derive(Debug, Clone, Copy)]
+struct Hook {
+    /// What it's stuck in.
+    anchor: Entity,
+    /// Where, relative to the anchor, so it follows moving platforms.
+    offset: Vec2<f32>,
+    /// Rope length, past which the owner is pulled back.
+    length: f32,
+}
+
+impl Hook {
+    /// Position of the hook in the world, if its anchor is still there.
+    fn position(&self, world: &World) -> Option<Vec2<f32>> {
+        world
+            .get::<&Transform>(self.anchor)
+            .ok()
+            .map(|t| t.translation + self.offset)
+    }
+}
+
+/// Radius of the hook, so it catches on edges a ray would slip past.
+const HOOK_RADIUS: f32 = 8.0;
+
+/// Fire a hook from `origin` along `direction`, stuck in the first solid
+/// thing in range.
+fn fire(world: &World, owner: Entity, origin: Vec2<f32>, direction: Vec2<f32>) -> Option<Hook> {
+    let stats = &stats().grappling;
+    let filter = QueryFilter::new(Layers::FIXED).exclude(Some(owner));
+    let hit = *physics::shape_cast(
+        world,
+        &Ball::new(HOOK_RADIUS),
+        origin,
+        0.0,
+        direction,
+        stats.range,
+        filter,
+    ).first()?;
+    let anchor = hit.entity;
+    let point = hit.position - hit.normal * HOOK_RADIUS;
+    let length = (point - origin).magnitude();
+    let transform = world.get::<&Transform>(anchor).ok()?;
+    Some(Hook {
+        anchor,
+        offset: point - transform.translation,
+        length,
+    })
+}
+
+/// ODM gear ability, see [AbilityDef].
+pub struct GrapplingAbility;
+
+impl AbilityDef for GrapplingAbility {
+    fn id(&self) -> &'static str {
+        "grappling"
+    }
+
+    fn instantiate(&self, world: &mut World, owner: Entity, binding: usize) -> Entity {
+        let mut rope = Sprite::new(Costume::Rope {
+            from: Default::default(),
+            to: Default::default(),
+        });
+        rope.visibility = Visibility::Hidden;
+        world.spawn((
+            Ability {
+                owner,
+                binding,
+                active: false,
+            },
+            Grapple::default(),
+            rope,
+            Cooldown::default(),
+        ))
+    }
+
+    /// System that fires, swings on and releases grappling hooks. The hook is
+    /// fired when the ability is pressed, and released when it's let go.
+    fn update(&self, world: &mut World, socket: &Socket, time: &Time) {
+        let stats = &stats().grappling;
+        type Query<'a> = (&'a Ability, &'a mut Grapple, &'a mut Cooldown, Option<&'a TimeScale>);
+
+        let mut changed = Vec::new();
+        for (_, (ability, grapple, cooldown, scale)) in &mut world.query::<Query>() {
+            let scale = scale
+                .map(|s| s.0)
+                .unwrap_or(1.0);
+            let dt = time.dt() * scale;
+            // Cooldown
+            cooldown.0 -= dt;
+            let pressed = ability.active && !grapple.was_active;
+            grapple.was_active = ability.active;
+            let Ok(origin) = world.get::<&Transform>(ability.owner).map(|t| t.translation) else {
+                continue;
+            };
+            // Fire
+            if pressed && grapple.hook.is_none() && cooldown.0 <= 0.0 {
+                let Ok(input) = world.get::<&Input>(ability.owner) else {
+                    continue;
+                };
+                grapple.hook = fire(world, ability.owner, origin, input.look_axis());
+                if grapple.hook.is_some() {
+                    changed.push((ability.owner, ability.binding, grapple.hook));
+                }
+                continue;
+            }
+            let Some(hook) = &mut grapple.hook else {
+                continue;
+            };
+            // Release on letting go, or when the rope has nothing to hold on
+            // to anymore, ie. broken platforms
+            let anchored = matches!(world.satisfies::<&Collider>(hook.anchor), Ok(true));
+            let position = hook.position(world).filter(|_| anchored);
+            let snapped = position.map_or(true, |p| (p - origin).magnitude() > stats.range * stats.snap);
+            if !ability.active || snapped {
+                grapple.hook = None;
+                *cooldown = Cooldown(stats.cooldown);
+                changed.push((ability.owner, ability.binding, None));
+                continue;
+            }
+            let Some(position) = position else {
+                continue;
+            };
+            // Reel in
+            hook.length = (hook.length - stats.reel * dt).max(stats.min_length);
+            // Rope only pulls when stretched
+            let delta = position - origin;
+            let distance = delta.magnitude();
+            let stretch = distance - hook.length;
+            let Some(normal) = delta.try_normalize(0.001).filter(|_| stretch > 0.0) else {
+                continue;
+            };
+            let Ok(mut kb) = world.get::<&mut KinematicBody>(ability.owner) else {
+                continue;
+            };
+            // Spring towards the hook, damping movement away from it
+            let away = -kb.velocity.dot(&normal);
+            let pull = stats.stiffness * stretch + stats.damping * away.max(0.0);
+            kb.velocity += normal * pull * dt;
+        }
+        for (owner, binding, hook) in changed {
+            // TODO: reliable transport
+            socket.broadcast(&Packet::Grapple {
+                owner,
+                hook: hook.map(|hook| (hook.anchor, hook.offset)),
+            });
+            if hook.is_none() {
+                if let Ok(id) = world.get::<&Connection>(owner) {
+                    socket.send(*id, &Packet::CooldownStart {
+                        binding,
+                        duration: stats.cooldown,
+                    });
+                }
+            }
+        }
+        // Newcomers need to see ropes already out
+        for &connection in socket.connections() {
+            for (_, (ability, grapple)) in world.query_mut::<(&Ability, &Grapple)>() {
+                if let Some(hook) = grapple.hook {
+                    socket.send(connection, &Packet::Grapple {
+                        owner: ability.owner,
+                        hook: Some((hook.anchor, hook.offset)),
+                    });
+                }
+            }
+        }
+    }
+
+    fn effects(&self, world: &mut World, socket: &Socket, _time: &Time) {
+        for (_, packet) in socket.packets() {
+            let Packet::Grapple { owner, hook } = *packet else {
+                continue;
+            };
+            for (_, (ability, grapple)) in world.query_mut::<(&Ability, &mut Grapple)>() {
+                if ability.owner == owner {
+                    grapple.hook = hook.map(|(anchor, offset)| Hook { anchor, offset, length: 0.0 });
+                }
+            }
+        }
+        // Rope from the owner to the hook
+        for (_, (ability, grapple, sprite)) in &mut world.query::<(&Ability, &Grapple, &mut Sprite)>() {
+            let Costume::Rope { from, to } = &mut sprite.costume else {
+                continue;
+            };
+            let ends = grapple.hook
+                .and_then(|hook| hook.position(world))
+                .zip(world.get::<&Transform>(ability.owner).ok());
+            sprite.visibility = match ends {
+                Some((hook, owner)) => {
+                    *from = owner.translation;
+                    *to = hook;
+                    Visibility::Shown
+                },
+                None => Visibility::Hidden,
+            };
+        }
+    }
+}
@@ -1,49 +1,105 @@
 use hecs::{World, Entity};
 
 use crate::{
-    math::Vec2,
+    math::{ Vec2, Rot2 },
     platform::{Time, Connection, Socket},
-    ability::Ability,
+    ability::{ Ability, GunStats },
     transform::Transform,
     input::Input, network::Packet,
+    timescale::TimeScale,
+    health::{ Damage, DamageSource },
+    bullet, physics::KinematicBody,
 };
 
 /// Component for a generic gun's stats.
 pub struct Gun {
     /// Gun's cooldown after each shot
     pub cooldown: Cooldown,
+    /// Seconds fire must be held before each shot goes off
+    pub charge: f32,
     /// Function that instantiates bullets 
-    pub shoot: fn(world: &mut World, owner: Entity, origin: Vec2<f32>, velocity: Vec2<f32>),
+    pub shoot: fn(world: &mut World, socket: &Socket, owner: Entity, origin: Vec2<f32>, direction: Vec2<f32>),
+}
+
+/// Shoot bullets as described by `stats`, from `origin` towards
+/// `direction`.
+pub fn shoot_bullets(world: &mut World, stats: &GunStats, owner: Entity, origin: Vec2<f32>, direction: Vec2<f32>) {
+    // TODO: this can be greatly optimized by simply sending the random seed
+    for _ in 0..stats.pellets {
+        let spread = Rot2::new(stats.spread * (fastrand::f32() - 0.5));
+        let velocity = stats.speed * (spread * direction);
+        let damage = Damage {
+            amount: stats.damage,
+            exclude: Some(owner),
+            destroy: true,
+            source: DamageSource::Player(owner),
+        };
+        world.spawn(bullet::prefab(origin, velocity, stats.lifetime)
+            .add(damage)
+            .build()
+        );
+    }
+    // Recoil
+    if let Ok(mut kb) = world.get::<&mut KinematicBody>(owner) {
+        kb.velocity -= direction * stats.recoil;
+    }
 }
 
 /// Component for current cooldown time.
 #[derive(Debug, Default, Clone, Copy)]
 pub struct Cooldown(pub f32);
 
-/// System that does the generic gun functionality
+/// Component for how long a [Gun]'s fire has been held, for guns that
+/// need charging.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Charge(pub f32);
+
+/// System that does the generic gun functionality. Clients only keep
+/// track of their own gun's charge and cooldown.
 pub fn gun_controller(world: &mut World, socket: &Socket, time: &Time) {
-    if cfg!(client) {
-        return;
-    }
     /// Queries all weapon holders
     type Query<'a> = (
         &'a Ability,            // Needed to test if active or not
         &'a Gun,                // Guns properties
         &'a mut Cooldown,       // Test and reset cooldown
         &'a mut Transform,      // Origin of bullets
+        Option<&'a mut Charge>, // Held fire, if it needs charging
+        Option<&'a TimeScale>,  // Dilates cooldown
     );
     let mut shots = Vec::new();
-    for (_, (ability, gun, cooldown, transform)) in &mut world.query::<Query>() {
+    for (_, (ability, gun, cooldown, transform, mut charge, scale)) in &mut world.query::<Query>() {
         // User input
         let Ok(input) = world.get::<&Input>(ability.owner) else {
             continue;
         };
+        let scale = scale
+            .map(|s| s.0)
+            .unwrap_or(1.0);
         // Cooldown
-        cooldown.0 -= time.dt();
+        cooldown.0 -= time.dt() * scale;
+        // Charging
+        let ready = ability.active && cooldown.0 <= 0.0 && input.fire();
+        let held = match charge.as_deref_mut() {
+            Some(charge) if ready => {
+                charge.0 += time.dt() * scale;
+                charge.0
+            },
+            Some(charge) => {
+                charge.0 = 0.0;
+                0.0
+            },
+            None => 0.0,
+        };
         // Shooting
-        if ability.active && cooldown.0 <= 0.0 && input.fire() {
-            shots.push((gun.shoot, ability.owner, transform.translation, input.look_axis()));
+        if ready && held >= gun.charge {
+            if let Some(charge) = charge.as_deref_mut() {
+                charge.0 = 0.0;
+            }
             *cooldown = gun.cooldown;
+            if cfg!(client) {
+                continue;
+            }
+            shots.push((gun.shoot, ability.owner, transform.translation, input.look_axis()));
             // "some" impatient threshold
             if cooldown.0 > 0.7 {
                 if let Ok(id) = world.get::<&Connection>(ability.owner) {
@@ -56,6 +112,6 @@ pub fn gun_controller(world: &mut World, socket: &Socket, time: &Time) {
         }
     }
     for (shoot, e, o, v) in shots {
-        (shoot)(world, e, o, v);
+        (shoot)(world, socket, e, o, v);
     }
 }
\ No newline at end of file
@@ -1,31 +1,76 @@
 use hecs::{ World, Entity, With };
 
 use crate::{
-    ability::{ Ability, Cooldown },
+    ability::{ AbilityDef, Ability, Stage, Cooldown, stats },
     platform::{Time, Socket, Connection},
     transform::Transform,
     math::vec2,
     render::{ Sprite, Costume }, bullet::TimeToLive, network::Packet, health::Health,
+    timescale::TimeScale,
 };
 
 /// Component that marks this entity as the heal ability
 struct Heal;
 
-pub fn instantiate(world: &mut World, owner: Entity, binding: usize) -> Entity {
-    world.spawn((
-        Ability {
-            owner,
-            binding,
-            active: false,
-        },
-        Heal,
-        Cooldown::default(),
-    ))
-}
+/// Heal ability, see [AbilityDef].
+pub struct HealAbility;
+
+impl AbilityDef for HealAbility {
+    fn id(&self) -> &'static str {
+        "heal"
+    }
+
+    fn instantiate(&self, world: &mut World, owner: Entity, binding: usize) -> Entity {
+        world.spawn((
+            Ability {
+                owner,
+                binding,
+                active: false,
+            },
+            Heal,
+            Cooldown::default(),
+        ))
+    }
 
-/// System that controls the heal ability
-pub fn heal_controller(world: &mut World, time: &Time, socket: &Socket) {
-    if cfg!(client) {
+    fn stage(&self) -> Stage {
+        Stage::Aim
+    }
+
+    /// System that controls the heal ability
+    fn update(&self, world: &mut World, socket: &Socket, time: &Time) {
+        type Query<'a> = With<(&'a Ability, &'a mut Cooldown, Option<&'a TimeScale>), &'a Heal>;
+        let stats = &stats().heal;
+
+        for (_, (ability, cooldown, scale)) in &mut world.query::<Query>() {
+            let scale = scale
+                .map(|s| s.0)
+                .unwrap_or(1.0);
+            // Cooldown
+            cooldown.0 -= time.dt() * scale;
+            // Trigger
+            if ability.active && cooldown.0 <= 0.0 {
+                if let Ok(transform) = world.get::<&Transform>(ability.owner) {
+                    // Sprite
+                    socket.broadcast(&Packet::EffectSpawn(Costume::Heal {
+                        position: transform.translation - vec2!(0.0, 30.0),
+                    }));
+                }
+                if let Ok(mut health) = world.get::<&mut Health>(ability.owner) {
+                    health.now = (health.now + stats.hitpoints).min(health.max);
+                    socket.broadcast(&Packet::EntityHealth(ability.owner, health.now));
+                }
+                *cooldown = Cooldown(stats.cooldown);
+                if let Ok(id) = world.get::<&Connection>(ability.owner) {
+                    socket.send(*id, &Packet::CooldownStart {
+                        binding: ability.binding,
+                        duration: cooldown.0,
+                    })
+                }
+            }
+        }
+    }
+
+    fn effects(&self, world: &mut World, socket: &Socket, _time: &Time) {
         for (_, packet) in socket.packets() {
             let Packet::EffectSpawn(costume) = packet else {
                 continue;
@@ -39,32 +84,5 @@ pub fn heal_controller(world: &mut World, time: &Time, socket: &Socket) {
                 TimeToLive::Frames(100)
             ));
         }
-        return;
     }
-    type Query<'a> = With<(&'a Ability, &'a mut Cooldown), &'a Heal>;
-
-    for (_, (ability, cooldown)) in &mut world.query::<Query>() {
-        // Cooldown
-        cooldown.0 -= time.dt();
-        // Trigger
-        if ability.active && cooldown.0 <= 0.0 {
-            if let Ok(transform) = world.get::<&Transform>(ability.owner) {
-                // Sprite
-                socket.broadcast(&Packet::EffectSpawn(Costume::Heal {
-                    position: transform.translation - vec2!(0.0, 30.0),
-                }));
-            }
-            if let Ok(mut health) = world.get::<&mut Health>(ability.owner) {
-                health.now = (health.now + 20.0).min(health.max);
-                socket.broadcast(&Packet::EntityHealth(ability.owner, health.now));
-            }
-            *cooldown = Cooldown(5.0);
-            if let Ok(id) = world.get::<&Connection>(ability.owner) {
-                socket.send(*id, &Packet::CooldownStart {
-                    binding: ability.binding,
-                    duration: cooldown.0,
-                })
-            }
-        }
-    }
-}
\ No newline at end of file
+}
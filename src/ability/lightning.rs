@@ -1,13 +1,15 @@
 use hecs::{ World, Entity };
 
 use crate::{
-    ability::{ Ability, Cooldown },
+    ability::{ AbilityDef, Ability, Cooldown, stats },
     platform::{Time, Socket},
     transform::Transform,
     render::{ Sprite, Costume },
     network::Packet,
-    physics::{self, Collider},
+    physics::{self, Collider, Collisions},
+    health::{ Damage, DamageSource, HitOnce },
     math::vec2, bullet::TimeToLive,
+    timescale::TimeScale,
 };
 
 /// Component that marks this entity as the el thor ability
@@ -26,22 +28,125 @@ enum Lightning {
     },
 }
 
-pub fn instantiate(world: &mut World, owner: Entity, binding: usize) -> Entity {
-    world.spawn((
-        Ability {
-            owner,
-            binding,
-            active: false,
-        },
-        Lightning::None,
-        Transform::default(),
-        Cooldown::default(),
-    ))
-}
+/// Lightning ability, see [AbilityDef].
+pub struct LightningAbility;
+
+impl AbilityDef for LightningAbility {
+    fn id(&self) -> &'static str {
+        "lightning"
+    }
+
+    fn instantiate(&self, world: &mut World, owner: Entity, binding: usize) -> Entity {
+        world.spawn((
+            Ability {
+                owner,
+                binding,
+                active: false,
+            },
+            Lightning::None,
+            Transform::default(),
+            Cooldown::default(),
+        ))
+    }
 
-/// System that controls the lightning ability
-pub fn lightning_controller(world: &mut World, time: &mut Time, socket: &Socket) {
-    if cfg!(client) {
+    /// System that controls the lightning ability
+    fn update(&self, world: &mut World, socket: &Socket, time: &Time) {
+        let stats = &stats().lightning;
+        let mut add = Vec::new();
+        let mut strike = Vec::new();
+        let mut destroy = Vec::new();
+        type Query<'a> = (
+            &'a Ability,
+            &'a mut Cooldown,
+            &'a mut Lightning,
+            Option<&'a TimeScale>,
+        );
+        for (_, (ability, cooldown, state, scale)) in &mut world.query::<Query>() {
+            let scale = scale
+                .map(|s| s.0)
+                .unwrap_or(1.0);
+            // Cooldown
+            cooldown.0 -= time.dt() * scale;
+            // State machine
+            *state = match state {
+                Lightning::None => {
+                    // Trigger
+                    if ability.active && cooldown.0 <= 0.0 {
+                        *cooldown = Cooldown(stats.cooldown);
+                        let entity = world.reserve_entity();
+                        if let Ok(transform) = world.get::<&Transform>(ability.owner) {
+                            add.push((ability.owner, entity, transform.translation));
+                        }
+                        // Transition
+                        Lightning::Loading { time_left: stats.delay, entity, }
+                    } else {
+                        Lightning::None
+                    }
+                },
+                Lightning::Loading { time_left, entity } => {
+                    // Transition
+                    if *time_left <= 0.0 {
+                        strike.push((ability.owner, *entity));
+                        Lightning::Active {
+                            time_left: stats.duration,
+                            entity: *entity,
+                        }
+                    } else {
+                        Lightning::Loading {
+                            time_left: *time_left - time.dt() * scale,
+                            entity: *entity,
+                        }
+                    }
+                },
+                Lightning::Active { time_left, entity } => {
+                    if *time_left <= 0.0 {
+                        destroy.push(*entity);
+                        Lightning::None
+                    } else {
+                        Lightning::Active {
+                            time_left: *time_left - time.dt() * scale,
+                            entity: *entity,
+                        }
+                    }
+                },
+            };
+        }
+        // Populate reserved entities
+        for (owner, reserved, position) in add {
+            // Impacton the ground
+            let position = physics::raycast(world, position, vec2!(0.0, -1.0), Some(owner))
+                .map(|(_, p)| p)
+                .unwrap_or(position);
+            world.spawn_at(reserved, (
+                Transform {
+                    // account for center
+                    translation: position + vec2!(0.0, 2500.0),
+                    rotation: 0.0
+                },
+                Collider::rect(stats.width, 5000.0),
+            ));
+            socket.broadcast(&Packet::EffectSpawn(Costume::Lightning { position }));
+        }
+        // Strike hurts everyone under it once, except the caster
+        for (owner, e) in strike {
+            // Not spawned if the caster was gone
+            let _ = world.insert(e, (
+                Damage {
+                    amount: stats.damage,
+                    exclude: Some(owner),
+                    destroy: false,
+                    source: DamageSource::Player(owner),
+                },
+                Collisions::default(),
+                HitOnce::default(),
+            ));
+        }
+        for e in destroy {
+            let _ = world.despawn(e);
+        }
+    }
+
+    fn effects(&self, world: &mut World, socket: &Socket, _time: &Time) {
         for (_, packet) in socket.packets() {
             let Packet::EffectSpawn(costume) = packet else {
                 continue;
@@ -56,70 +161,5 @@ pub fn lightning_controller(world: &mut World, time: &mut Time, socket: &Socket)
                 TimeToLive::Seconds(5.0),
             ));
         }
-        return;
     }
-    let mut add = Vec::new();
-    for (_, (ability, cooldown, state)) in &mut world.query::<(&Ability, &mut Cooldown, &mut Lightning)>() {
-        // Cooldown
-        cooldown.0 -= time.dt();
-        // State machine
-        *state = match state {
-            Lightning::None => {
-                // Trigger
-                if ability.active && cooldown.0 <= 0.0 {
-                    *cooldown = Cooldown(5.0);
-                    let entity = world.reserve_entity();
-                    if let Ok(transform) = world.get::<&Transform>(ability.owner) {
-                        add.push((ability.owner, entity, transform.translation));
-                    }
-                    // Transition
-                    Lightning::Loading { time_left: 3.0, entity, }
-                } else {
-                    Lightning::None
-                }
-            },
-            Lightning::Loading { time_left, entity } => {
-                // Transition
-                if *time_left <= 0.0 {
-                    // TODO: give entity a damage component here
-                    Lightning::Active {
-                        time_left: 0.5,
-                        entity: *entity,
-                    }
-                } else {
-                    Lightning::Loading {
-                        time_left: *time_left - time.dt(),
-                        entity: *entity,
-                    }
-                }
-            },
-            Lightning::Active { time_left, entity } => {
-                if *time_left <= 0.0 {
-                    // TODO: delete entity here
-                    Lightning::None
-                } else {
-                    Lightning::Active {
-                        time_left: *time_left - time.dt(),
-                        entity: *entity,
-                    }
-                }
-            },
-        };
-    }
-    // Populate reserved entities
-    for (owner, reserved, position) in add {
-        // Impacton the ground
-        let position = physics::raycast(world, position, vec2!(0.0, -1.0), Some(owner))
-            .map(|(_, p)| p)
-            .unwrap_or(position);
-        world.spawn_at(reserved, (
-            Transform {
-                // account for center
-                translation: position + vec2!(0.0, 2500.0),
-                rotation: 0.0
-            },
-            Collider::rect(100.0, 5000.0),
-        ));
-        socket.broadcast(&Packet::EffectSpawn(Costume::Lightning { position }));
-    }
-}
\ No newline at end of file
+}
@@ -1,19 +1,17 @@
 pub use gun::*;
+pub use stats::*;
 pub use shield::{ Shield, position_shield };
-pub use push::push_controller;
-pub use freeze::{ TimeScale, freeze_controller };
-pub use lightning::lightning_controller;
-pub use bubble::{ BubbleShield, bubble_shield_controller };
-pub use heal::heal_controller;
 
 use hecs::{ Entity, World };
 
 use crate::{
-    platform::Socket,
+    platform::{ Socket, Time },
     input::Input,
-    network::Packet
+    network::Packet,
+    timescale::TimeScale,
 };
 
+mod stats;
 mod gun;
 mod shotgun;
 mod rifle;
@@ -24,20 +22,111 @@ mod freeze;
 mod lightning;
 mod bubble;
 mod heal;
+mod sniper;
+mod grapple;
 
-/// Complete enumeration of all ability types
+/// An ability, fully defined by its own module: how it's spawned, what
+/// it does on the server and what clients show of it.
+///
+/// Its sprites are [Costume](crate::render::Costume) variants, which the
+/// platform draws by tag, so those still live in `render.rs` and
+/// `platform/web.ts` next to every other costume.
+pub trait AbilityDef: Sync {
+    /// Id of the ability in `abilities.toml` and player decks.
+    fn id(&self) -> &'static str;
+
+    /// Spawn the ability for `owner`, bound to slot `binding`.
+    fn instantiate(&self, world: &mut World, owner: Entity, binding: usize) -> Entity;
+
+    /// Server side system, ran every tick.
+    fn update(&self, _world: &mut World, _socket: &Socket, _time: &Time) {}
+
+    /// Client side system, ran every tick, ie. showing effects the server
+    /// sent.
+    fn effects(&self, _world: &mut World, _socket: &Socket, _time: &Time) {}
+
+    /// Client side system animating the ability's sprites, ran after the
+    /// frame has been simulated.
+    fn animate(&self, _world: &mut World) {}
+
+    /// Where in the frame [update](AbilityDef::update) and
+    /// [effects](AbilityDef::effects) run.
+    fn stage(&self) -> Stage {
+        Stage::Movement
+    }
+
+    /// Name of the ability in `abilities.toml`, see [Metadata].
+    fn metadata(&self) -> &'static Metadata {
+        metadata(self.id())
+    }
+}
+
+/// Where in the frame an ability's systems run, see [update_abilities].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// Before physics, ie. abilities moving players or pushing things around
+    Movement,
+    /// Right after guns fired
+    Guns,
+    /// After abilities followed their owner's look direction
+    Aim,
+}
+
+/// Every ability there is, systems of the same [Stage] run in this order.
+pub static REGISTRY: &[&dyn AbilityDef] = &[
+    &shotgun::ShotgunAbility,
+    &rifle::AssaultRifleAbility,
+    &pistols::DualGunAbility,
+    &shield::ShieldAbility,
+    &bubble::BubbleShieldAbility,
+    &push::PushAbility,
+    &freeze::TimeFreezeAbility,
+    &lightning::LightningAbility,
+    &heal::HealAbility,
+    &sniper::SniperAbility,
+    &grapple::GrapplingAbility,
+];
+
+/// Handle to an ability in the [REGISTRY].
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-#[repr(u32)]
-pub enum AbilityKind {
-    Shotgun,
-    AssaultRifle,
-    DualGun,
-    Shield,
-    Push,
-    Freeze,
-    Lightning,
-    BubbleShield,
-    Heal,
+#[repr(transparent)]
+pub struct AbilityKind(u32);
+
+impl AbilityKind {
+    /// Ability with the given id, if there is one.
+    pub fn from_id(id: &str) -> Option<Self> {
+        REGISTRY
+            .iter()
+            .position(|ability| ability.id() == id)
+            .map(|i| Self(i as u32))
+    }
+
+    /// The ability itself.
+    pub fn get(self) -> &'static dyn AbilityDef {
+        REGISTRY[self.0 as usize]
+    }
+}
+
+/// Panic early on malformed ability stats, abilities missing from
+/// `abilities.toml` or sharing an id.
+pub fn validate() {
+    stats();
+    for (i, ability) in REGISTRY.iter().enumerate() {
+        ability.metadata();
+        if REGISTRY[..i].iter().any(|other| other.id() == ability.id()) {
+            panic!("duplicate ability id {}", ability.id());
+        }
+    }
+}
+
+/// Parse a deck of 4 comma separated ability ids.
+pub fn parse_deck(deck: &str) -> Option<[AbilityKind; 4]> {
+    let mut kinds = deck.split(',').map(|id| AbilityKind::from_id(id.trim()));
+    let deck = [kinds.next()??, kinds.next()??, kinds.next()??, kinds.next()??];
+    match kinds.next() {
+        Some(_) => None,
+        None => Some(deck),
+    }
 }
 
 /// Component that marks this entity as an ability
@@ -52,16 +141,32 @@ pub struct Ability {
 }
 
 pub fn instantiate(world: &mut World, owner: Entity, binding: usize, kind: AbilityKind) -> Entity {
-    match kind {
-        AbilityKind::Shotgun => shotgun::instantiate(world, owner, binding),
-        AbilityKind::AssaultRifle => rifle::instantiate(world, owner, binding),
-        AbilityKind::DualGun => pistols::instantiate(world, owner, binding),
-        AbilityKind::Shield => shield::instantiate(world, owner, binding),
-        AbilityKind::Push => push::instantiate(world, owner, binding),
-        AbilityKind::Freeze => freeze::instantiate(world, owner, binding),
-        AbilityKind::Lightning => lightning::instantiate(world, owner, binding),
-        AbilityKind::BubbleShield => bubble::instantiate(world, owner, binding),
-        AbilityKind::Heal => heal::instantiate(world, owner, binding),
+    let e = kind.get().instantiate(world, owner, binding);
+    // Abilities are dilated along with their owner
+    world.insert_one(e, TimeScale::default()).unwrap();
+    e
+}
+
+/// System that runs the [AbilityDef::update] of every ability in `stage` on
+/// the server, and [AbilityDef::effects] on clients.
+pub fn update_abilities(world: &mut World, socket: &Socket, time: &Time, stage: Stage) {
+    for ability in REGISTRY.iter().filter(|ability| ability.stage() == stage) {
+        if cfg!(server) {
+            ability.update(world, socket, time);
+        }
+        if cfg!(client) {
+            ability.effects(world, socket, time);
+        }
+    }
+}
+
+/// System that runs every ability's [AbilityDef::animate] on clients.
+pub fn animate_abilities(world: &mut World) {
+    if cfg!(server) {
+        return;
+    }
+    for ability in REGISTRY {
+        ability.animate(world);
     }
 }
 
@@ -1,47 +1,44 @@
 use hecs::{ Entity, World };
 
 use crate::{
-    ability::{ Ability, Gun, Cooldown },
+    ability::{ AbilityDef, Ability, Gun, Cooldown, shoot_bullets, stats },
     transform::{ Transform, Parent, LocalPosition },
     render::{ Sprite, Costume },
-    math::{ Rot2, vec2 },
+    math::vec2,
     input::FollowLookDirection,
-    health::Damage,
-    bullet,
 };
 
-pub fn instantiate(world: &mut World, owner: Entity, binding: usize) -> Entity {
-    world.spawn((
-        Ability {
-            owner,
-            binding,
-            active: false,
-        },
-        Gun {
-            // TODO these should come from `abilities.toml`
-            cooldown: Cooldown(0.2),
-            shoot: |world, owner, origin, velocity| {
-                let spread = Rot2::new(0.01 * (fastrand::f32() - 0.5));
-                let velocity = 1500.0 * (spread * velocity);
-                let damage = Damage {
-                    amount: 5.0,
-                    exclude: Some(owner),
-                    destroy: true,
-                };
-                world.spawn(bullet::prefab(origin, velocity, 1.5)
-                    .add(damage)
-                    .build()
-                );
+/// Dual pistols ability, see [AbilityDef].
+pub struct DualGunAbility;
+
+impl AbilityDef for DualGunAbility {
+    fn id(&self) -> &'static str {
+        "dual-gun"
+    }
+
+    fn instantiate(&self, world: &mut World, owner: Entity, binding: usize) -> Entity {
+        world.spawn((
+            Ability {
+                owner,
+                binding,
+                active: false,
+            },
+            Gun {
+                cooldown: Cooldown(stats().dual_gun.cooldown),
+                charge: 0.0,
+                shoot: |world, _, owner, origin, direction| {
+                    shoot_bullets(world, &stats().dual_gun, owner, origin, direction);
+                },
             },
-        },
-        Sprite::new(Costume::DualGun {
-            position: Default::default(),
-            rotation: Default::default(),
-        }),
-        Cooldown::default(),
-        Transform::default(),
-        Parent(owner),
-        FollowLookDirection(owner),
-        LocalPosition(vec2!(0.0, 0.0)),
-    ))
-}
\ No newline at end of file
+            Sprite::new(Costume::DualGun {
+                position: Default::default(),
+                rotation: Default::default(),
+            }),
+            Cooldown::default(),
+            Transform::default(),
+            Parent(owner),
+            FollowLookDirection(owner),
+            LocalPosition(vec2!(0.0, 0.0)),
+        ))
+    }
+}
@@ -1,31 +1,84 @@
 use hecs::{ World, Entity, With };
 
 use crate::{
-    ability::{ Ability, Cooldown },
+    ability::{ AbilityDef, Ability, Cooldown, stats },
     platform::{Time, Socket, Connection},
     transform::Transform,
-    physics::KinematicBody,
+    physics::{ self, KinematicBody, Layers, QueryFilter },
     render::{ Sprite, Costume }, bullet::TimeToLive, network::Packet,
+    timescale::TimeScale,
 };
 
 /// Component that marks this entity as the push ability
 struct Push;
 
-pub fn instantiate(world: &mut World, owner: Entity, binding: usize) -> Entity {
-    world.spawn((
-        Ability {
-            owner,
-            binding,
-            active: false,
-        },
-        Push,
-        Cooldown::default(),
-    ))
-}
+/// Push ability, see [AbilityDef].
+pub struct PushAbility;
+
+impl AbilityDef for PushAbility {
+    fn id(&self) -> &'static str {
+        "push"
+    }
+
+    fn instantiate(&self, world: &mut World, owner: Entity, binding: usize) -> Entity {
+        world.spawn((
+            Ability {
+                owner,
+                binding,
+                active: false,
+            },
+            Push,
+            Cooldown::default(),
+        ))
+    }
+
+    /// System that controls the almighty push
+    fn update(&self, world: &mut World, socket: &Socket, time: &Time) {
+        /// Queries all weapon holders
+        type Query<'a> = With<(&'a Ability, &'a mut Cooldown, Option<&'a TimeScale>), &'a Push>;
+        let stats = &stats().push;
 
-/// System that controls the almighty push
-pub fn push_controller(world: &mut World, time: &Time, socket: &Socket) {
-    if cfg!(client) {
+        let mut pushes = Vec::new();
+        for (_, (ability, cooldown, scale)) in &mut world.query::<Query>() {
+            let scale = scale
+                .map(|s| s.0)
+                .unwrap_or(1.0);
+            // Cooldown
+            cooldown.0 -= time.dt() * scale;
+            // Trigger
+            if ability.active && cooldown.0 <= 0.0 {
+                if let Ok(transform) = world.get::<&Transform>(ability.owner) {
+                    pushes.push((ability.owner, transform.translation));
+                }
+                *cooldown = Cooldown(stats.cooldown);
+                if let Ok(id) = world.get::<&Connection>(ability.owner) {
+                    socket.send(*id, &Packet::CooldownStart {
+                        binding: ability.binding,
+                        duration: cooldown.0,
+                    })
+                }
+            }
+        }
+        for (owner, origin) in pushes {
+            // Sprite
+            socket.broadcast(&Packet::EffectSpawn(Costume::Push {
+                position: origin,
+                radius: stats.radius,
+            }));
+            // Push everything in range
+            let filter = QueryFilter::new(Layers::KINEMATIC).exclude(Some(owner));
+            for e in physics::overlap_circle(world, origin, stats.radius, filter) {
+                let Ok((t, kb)) = world.query_one_mut::<(&Transform, &mut KinematicBody)>(e) else {
+                    continue;
+                };
+                if let Some(delta) = (t.translation - origin).try_normalize(0.01) {
+                    kb.velocity = stats.speed * delta;
+                }
+            }
+        }
+    }
+
+    fn effects(&self, world: &mut World, socket: &Socket, _time: &Time) {
         for (_, packet) in socket.packets() {
             let Packet::EffectSpawn(costume) = packet else {
                 continue;
@@ -39,37 +92,5 @@ pub fn push_controller(world: &mut World, time: &Time, socket: &Socket) {
                 TimeToLive::Frames(100)
             ));
         }
-        return;
-    }
-    /// Queries all weapon holders
-    type Query<'a> = With<(&'a Ability, &'a mut Cooldown), &'a Push>;
-
-    let mut pushes = Vec::new();
-    for (_, (ability, cooldown)) in &mut world.query::<Query>() {
-        // Cooldown
-        cooldown.0 -= time.dt();
-        // Trigger
-        if ability.active && cooldown.0 <= 0.0 {
-            if let Ok(transform) = world.get::<&Transform>(ability.owner) {
-                pushes.push(transform.translation);
-            }
-            *cooldown = Cooldown(15.0);
-            if let Ok(id) = world.get::<&Connection>(ability.owner) {
-                socket.send(*id, &Packet::CooldownStart {
-                    binding: ability.binding,
-                    duration: cooldown.0,
-                })
-            }
-        }
     }
-    for origin in pushes {
-        // Sprite
-        socket.broadcast(&Packet::EffectSpawn(Costume::Push { position: origin }));
-        // Push everything
-        for (_, (t, kb)) in world.query_mut::<(&Transform, &mut KinematicBody)>() {
-            if let Some(delta) = (t.translation - origin).try_normalize(0.01) {
-                kb.velocity = 2000.0 * delta;
-            }
-        }
-    }
-}
\ No newline at end of file
+}
@@ -1,7 +1,7 @@
 use hecs::{ Entity, World };
 
 use crate::{
-    ability::Ability,
+    ability::{ AbilityDef, Ability, stats },
     render::{ Sprite, Costume },
     transform::{ Transform, LocalPosition },
     input::FollowLookDirection,
@@ -12,23 +12,33 @@ use crate::{
 // Special parent with offsetted pivot
 pub struct Shield(pub Entity);
 
-pub fn instantiate(world: &mut World, owner: Entity, binding: usize) -> Entity {
-    world.spawn((
-        Ability {
-            owner,
-            binding,
-            active: false,
-        },
-        Sprite::new(Costume::Shield {
-            position: Default::default(),
-            rotation: Default::default(),
-        }),
-        Shield(owner),
-        Transform::default(),
-        Collider::rect(25.0, 40.0),
-        LocalPosition(vec2!(25.0, 0.0)),
-        FollowLookDirection(owner),
-    ))
+/// Shield ability, see [AbilityDef].
+pub struct ShieldAbility;
+
+impl AbilityDef for ShieldAbility {
+    fn id(&self) -> &'static str {
+        "shield"
+    }
+
+    fn instantiate(&self, world: &mut World, owner: Entity, binding: usize) -> Entity {
+        let stats = &stats().shield;
+        world.spawn((
+            Ability {
+                owner,
+                binding,
+                active: false,
+            },
+            Sprite::new(Costume::Shield {
+                position: Default::default(),
+                rotation: Default::default(),
+            }),
+            Shield(owner),
+            Transform::default(),
+            Collider::rect(stats.width, stats.height),
+            LocalPosition(vec2!(stats.offset, 0.0)),
+            FollowLookDirection(owner),
+        ))
+    }
 }
 
 /// System that positions the shield
@@ -1,54 +1,44 @@
 use hecs::{ Entity, World };
 
 use crate::{
-    ability::{ Ability, Gun, Cooldown },
+    ability::{ AbilityDef, Ability, Gun, Cooldown, shoot_bullets, stats },
     transform::{ Transform, Parent, LocalPosition },
     render::{ Sprite, Costume },
-    math::{ Rot2, vec2 },
+    math::vec2,
     input::FollowLookDirection,
-    health::Damage,
-    bullet, physics::KinematicBody,
 };
 
-pub fn instantiate(world: &mut World, owner: Entity, binding: usize) -> Entity {
-    world.spawn((
-        Ability {
-            owner,
-            binding,
-            active: false,
-        },
-        Gun {
-            // TODO: these should come from `abilities.toml`
-            cooldown: Cooldown(1.0),
-            shoot: |world, owner, origin, velocity| {
-                // TODO: this can be greatly optimized by simply sending the random seed
-                for _ in 0..10 {
-                    let spread = Rot2::new(0.1 * (fastrand::f32() - 0.5));
-                    let velocity = 1500.0 * (spread * velocity);
-                    let damage = Damage {
-                        amount: 5.0,
-                        exclude: Some(owner),
-                        destroy: true,
-                    };
-                    world.spawn(bullet::prefab(origin, velocity, 0.3)
-                        .add(damage)
-                        .build()
-                    );
-                }
-                // Recoil
-                if let Ok(mut kb) = world.get::<&mut KinematicBody>(owner) {
-                    kb.velocity -= velocity * 250.0;
-                }
+/// Shotgun ability, see [AbilityDef].
+pub struct ShotgunAbility;
+
+impl AbilityDef for ShotgunAbility {
+    fn id(&self) -> &'static str {
+        "shotgun"
+    }
+
+    fn instantiate(&self, world: &mut World, owner: Entity, binding: usize) -> Entity {
+        world.spawn((
+            Ability {
+                owner,
+                binding,
+                active: false,
+            },
+            Gun {
+                cooldown: Cooldown(stats().shotgun.cooldown),
+                charge: 0.0,
+                shoot: |world, _, owner, origin, direction| {
+                    shoot_bullets(world, &stats().shotgun, owner, origin, direction);
+                },
             },
-        },
-        Sprite::new(Costume::Shotgun {
-            position: Default::default(),
-            rotation: Default::default(),
-        }),
-        Cooldown::default(),
-        Transform::default(),
-        Parent(owner),
-        FollowLookDirection(owner),
-        LocalPosition(vec2!(0.0, 0.0)),
-    ))
-}
\ No newline at end of file
+            Sprite::new(Costume::Shotgun {
+                position: Default::default(),
+                rotation: Default::default(),
+            }),
+            Cooldown::default(),
+            Transform::default(),
+            Parent(owner),
+            FollowLookDirection(owner),
+            LocalPosition(vec2!(0.0, 0.0)),
+        ))
+    }
+}
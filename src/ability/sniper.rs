@@ -0,0 +1,136 @@
+use hecs::{ Entity, World };
+
+use crate::{
+    ability::{ AbilityDef, Ability, Stage, Gun, Cooldown, Charge, Shield, stats },
+    transform::{ Transform, Parent, LocalPosition },
+    render::{ Sprite, Costume },
+    math::{ Vec2, vec2 },
+    input::{ FollowLookDirection, Input },
+    platform::{ Socket, Time },
+    network::Packet,
+    health::{ Health, DamageSource },
+    physics,
+    pickup::Boosts,
+    bullet::TimeToLive,
+};
+
+/// Most things a shot can pass through, ie. inactive abilities.
+const MAX_PIERCE: usize = 8;
+
+/// First thing hit by a shot from `origin` along `direction`, passing
+/// through the shooter's and inactive abilities, and where.
+fn hitscan(world: &World, owner: Entity, origin: Vec2<f32>, direction: Vec2<f32>) -> Option<(Entity, Vec2<f32>)> {
+    let mut from = (origin, owner);
+    for _ in 0..MAX_PIERCE {
+        let (e, point) = physics::raycast(world, from.0, direction, Some(from.1))?;
+        let passes = world
+            .get::<&Ability>(e)
+            .map(|ability| ability.owner == owner || !ability.active)
+            .unwrap_or(false);
+        if !passes {
+            return Some((e, point));
+        }
+        from = (point, e);
+    }
+    None
+}
+
+/// Hitscan shot, damage falls off with distance.
+fn shoot(world: &mut World, socket: &Socket, owner: Entity, origin: Vec2<f32>, direction: Vec2<f32>) {
+    let stats = &stats().sniper;
+    let Some(direction) = direction.try_normalize(0.001) else {
+        return;
+    };
+    let max = origin + direction * stats.range;
+    let hit = hitscan(world, owner, origin, direction)
+        .filter(|(_, point)| (point - origin).magnitude() <= stats.range);
+    socket.broadcast(&Packet::EffectSpawn(Costume::Tracer {
+        from: origin,
+        to: hit.map(|(_, point)| point).unwrap_or(max),
+    }));
+    let Some((target, point)) = hit else {
+        return;
+    };
+    // Shields block
+    if matches!(world.satisfies::<&Shield>(target), Ok(true)) {
+        return;
+    }
+    let boost = world
+        .get::<&Boosts>(owner)
+        .map(|b| b.damage_factor())
+        .unwrap_or(1.0);
+    let amount = stats.damage_at((point - origin).magnitude()) * boost;
+    if let Ok(mut health) = world.get::<&mut Health>(target) {
+        health.damage(amount, DamageSource::Player(owner));
+        socket.broadcast(&Packet::EntityHealth(target, health.now));
+    }
+}
+
+/// Sniper ability, see [AbilityDef].
+pub struct SniperAbility;
+
+impl AbilityDef for SniperAbility {
+    fn id(&self) -> &'static str {
+        "sniper"
+    }
+
+    fn instantiate(&self, world: &mut World, owner: Entity, binding: usize) -> Entity {
+        let stats = &stats().sniper;
+        world.spawn((
+            Ability {
+                owner,
+                binding,
+                active: false,
+            },
+            Gun {
+                cooldown: Cooldown(stats.cooldown),
+                charge: stats.charge,
+                shoot,
+            },
+            Sprite::new(Costume::Sniper {
+                position: Default::default(),
+                rotation: Default::default(),
+                sight: 0.0,
+            }),
+            Cooldown::default(),
+            Charge::default(),
+            Transform::default(),
+            Parent(owner),
+            FollowLookDirection(owner),
+            LocalPosition(vec2!(0.0, 0.0)),
+        ))
+    }
+
+    fn stage(&self) -> Stage {
+        Stage::Guns
+    }
+
+    /// Shows sniper tracers, and the sight of the player's own sniper
+    /// growing as it charges.
+    fn effects(&self, world: &mut World, socket: &Socket, _time: &Time) {
+        for (_, packet) in socket.packets() {
+            let Packet::EffectSpawn(costume) = packet else {
+                continue;
+            };
+            if !matches!(costume, Costume::Tracer { .. }) {
+                continue;
+            }
+            world.spawn((
+                Sprite::new(costume.clone()),
+                TimeToLive::Frames(20),
+            ));
+        }
+        let stats = &stats().sniper;
+        for (_, (ability, gun, charge, sprite)) in &mut world.query::<(&Ability, &Gun, &Charge, &mut Sprite)>() {
+            let Costume::Sniper { sight, .. } = &mut sprite.costume else {
+                continue;
+            };
+            // Only the local player has input
+            let aiming = ability.active && matches!(world.satisfies::<&Input>(ability.owner), Ok(true));
+            *sight = match aiming {
+                true => stats.range * (charge.0 / gun.charge).min(1.0),
+                false => 0.0,
+            };
+        }
+    }
+}
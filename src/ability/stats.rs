@@ -0,0 +1,258 @@
+//! Gameplay numbers of every ability, from the `gameplay` table of its
+//! entry in `assets/abilities.toml`, and its name. Menus read the rest of
+//! the entry themselves.
+
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use serde::{ Deserialize, Deserializer, de::DeserializeOwned };
+
+const SRC: &str = include_str!("../assets/abilities.toml");
+
+static STATS: Lazy<Stats> = Lazy::new(parse);
+static METADATA: Lazy<HashMap<String, Metadata>> = Lazy::new(parse);
+
+/// The `abilities` table of `abilities.toml`.
+fn parse<T: DeserializeOwned>() -> T {
+    match toml::from_str(SRC) {
+        Ok(Abilities { abilities }) => abilities,
+        Err(e) => panic!("malformed abilities.toml: {}", e.message()),
+    }
+}
+
+/// Every ability's stats, parsed on first use.
+pub fn stats() -> &'static Stats {
+    &STATS
+}
+
+/// Metadata of the ability with the given id.
+pub fn metadata(id: &str) -> &'static Metadata {
+    match METADATA.get(id) {
+        Some(metadata) => metadata,
+        None => panic!("ability {id} missing from abilities.toml"),
+    }
+}
+
+#[derive(Deserialize)]
+struct Abilities<T> {
+    abilities: T,
+}
+
+/// What the game itself needs to know of an ability besides its stats,
+/// ie. its name for logs.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Metadata {
+    pub name: String,
+}
+
+/// Stats of each ability, by their id in `abilities.toml`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Stats {
+    #[serde(deserialize_with = "gameplay")]
+    pub assault_rifle: GunStats,
+    #[serde(deserialize_with = "gameplay")]
+    pub shotgun: GunStats,
+    #[serde(deserialize_with = "gameplay")]
+    pub dual_gun: GunStats,
+    #[serde(deserialize_with = "gameplay")]
+    pub sniper: SniperStats,
+    #[serde(deserialize_with = "gameplay")]
+    pub heal: HealStats,
+    #[serde(deserialize_with = "gameplay")]
+    pub push: PushStats,
+    #[serde(deserialize_with = "gameplay")]
+    pub time_freeze: FreezeStats,
+    #[serde(deserialize_with = "gameplay")]
+    pub shield: ShieldStats,
+    #[serde(deserialize_with = "gameplay")]
+    pub bubble_shield: BubbleStats,
+    #[serde(deserialize_with = "gameplay")]
+    pub grappling: GrappleStats,
+    #[serde(deserialize_with = "gameplay")]
+    pub lightning: LightningStats,
+}
+
+/// Pick the `gameplay` table out of an ability's entry.
+fn gameplay<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    #[derive(Deserialize)]
+    struct Entry<T> {
+        gameplay: T,
+    }
+    Entry::deserialize(deserializer).map(|entry| entry.gameplay)
+}
+
+/// Stats of a gun shooting bullets.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GunStats {
+    /// Seconds between shots.
+    pub cooldown: f32,
+    /// Hitpoints taken by each bullet.
+    pub damage: f32,
+    /// Bullet speed.
+    pub speed: f32,
+    /// Seconds before bullets vanish.
+    pub lifetime: f32,
+    /// Angle in radians bullets are randomly spread over.
+    #[serde(default)]
+    pub spread: f32,
+    /// Bullets per shot.
+    #[serde(default = "GunStats::pellets")]
+    pub pellets: usize,
+    /// How hard the shooter is kicked back.
+    #[serde(default)]
+    pub recoil: f32,
+}
+
+impl GunStats {
+    fn pellets() -> usize {
+        1
+    }
+}
+
+/// Stats of a hitscan gun.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SniperStats {
+    pub cooldown: f32,
+    /// Seconds fire must be held before shooting.
+    pub charge: f32,
+    /// Hitpoints taken up close.
+    pub damage: f32,
+    /// Furthest distance anything can be hit at.
+    pub range: f32,
+    /// Distance past which damage starts falling off.
+    pub falloff: f32,
+    /// Fraction of the damage left at the end of the range.
+    pub min_damage: f32,
+}
+
+impl SniperStats {
+    /// Hitpoints taken by a target `distance` away, falling off linearly
+    /// from full damage to `min_damage`.
+    pub fn damage_at(&self, distance: f32) -> f32 {
+        if distance > self.range {
+            return 0.0;
+        }
+        let t = ((distance - self.falloff) / (self.range - self.falloff)).clamp(0.0, 1.0);
+        self.damage * (1.0 - t * (1.0 - self.min_damage))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HealStats {
+    pub cooldown: f32,
+    /// Hitpoints restored.
+    pub hitpoints: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PushStats {
+    pub cooldown: f32,
+    /// Distance from the caster within which bodies are pushed.
+    pub radius: f32,
+    /// Speed bodies are pushed away at.
+    pub speed: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FreezeStats {
+    pub cooldown: f32,
+    /// Seconds the freeze lasts.
+    pub duration: f32,
+    /// Time scale of everyone but the caster.
+    pub scale: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ShieldStats {
+    pub width: f32,
+    pub height: f32,
+    /// Distance in front of the owner.
+    pub offset: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BubbleStats {
+    /// Seconds before coming back once shrunk down.
+    pub cooldown: f32,
+    /// Starting radius.
+    pub radius: f32,
+    /// Radius at which it pops.
+    pub min_radius: f32,
+    /// Radius lost per second while active.
+    pub shrink: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GrappleStats {
+    /// Seconds before firing again once released.
+    pub cooldown: f32,
+    /// Furthest the hook reaches.
+    pub range: f32,
+    /// Speed the rope is reeled in at.
+    pub reel: f32,
+    /// Shortest the rope gets.
+    pub min_length: f32,
+    /// Pull per unit the rope is stretched.
+    pub stiffness: f32,
+    /// Pull per unit of speed away from the hook.
+    pub damping: f32,
+    /// Multiple of the range at which the rope snaps, ie. teleporting.
+    pub snap: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LightningStats {
+    pub cooldown: f32,
+    /// Hitpoints taken from everyone struck.
+    pub damage: f32,
+    /// Seconds between casting and the strike landing.
+    pub delay: f32,
+    /// Seconds the strike lasts.
+    pub duration: f32,
+    /// Width of the strike.
+    pub width: f32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn abilities_toml_parses() {
+        let stats = stats();
+        assert!(stats.shotgun.pellets > 1);
+        assert_eq!(stats.assault_rifle.pellets, 1);
+        assert!(stats.bubble_shield.min_radius < stats.bubble_shield.radius);
+    }
+
+    #[test]
+    fn metadata_parses() {
+        assert_eq!(metadata("assault-rifle").name, "Assault Rifle");
+        assert!(!metadata("sniper").name.is_empty());
+    }
+
+    #[test]
+    fn sniper_damage_falls_off() {
+        let sniper = &stats().sniper;
+        assert_eq!(sniper.damage_at(0.0), sniper.damage);
+        assert_eq!(sniper.damage_at(sniper.falloff), sniper.damage);
+        let end = sniper.damage_at(sniper.range);
+        assert!((end - sniper.damage * sniper.min_damage).abs() < 1e-3);
+        assert!(sniper.damage_at((sniper.falloff + sniper.range) / 2.0) > end);
+        assert_eq!(sniper.damage_at(sniper.range + 1.0), 0.0);
+    }
+}
@@ -7,8 +7,10 @@ use crate::{
     network::Packet,
     platform::{ Socket, Time },
     render::{ Sprite, Costume },
-    health::{ Damage, Health },
+    health::{ Damage, Health, DamageSource, HitOnce },
     ability::{ Shield, Ability },
+    timescale::TimeScale,
+    pickup::Boosts,
 };
 
 // TODO: this is a lazy workaround for now, but a system like this could be
@@ -38,6 +40,7 @@ pub fn prefab(origin: Vec2<f32>, velocity: Vec2<f32>, ttl: f32) -> EntityBuilder
             ..Default::default()
         },
         TimeToLive::Seconds(ttl),
+        TimeScale::default(),
     ));
     // Replicate on the network.
     if cfg!(server) {
@@ -76,14 +79,14 @@ pub fn network_instantiate(world: &mut World, socket: &Socket) {
 pub fn despawn_time_to_live(world: &mut World, time: &Time) {
     let mut kill = Vec::new();
     
-    for (e, ttl) in world.query_mut::<&mut TimeToLive>() {
+    for (e, (ttl, scale)) in world.query_mut::<(&mut TimeToLive, Option<&TimeScale>)>() {
         let dead = match ttl {
             TimeToLive::Frames(t) => {
                 *t -= 1;
                 *t <= 0
             },
             TimeToLive::Seconds(t) => {
-                *t -= time.dt();
+                *t -= time.dt() * scale.map(|s| s.0).unwrap_or(1.0);
                 *t <= 0.0
             },
         };
@@ -110,13 +113,17 @@ pub fn impact_and_damage(world: &mut World, socket: &Socket) {
     }
     let mut destroy = Vec::new();
     // Query bullets
-    for (e1, (damage, collisions)) in &mut world.query::<(&Damage, &Collisions)>() {
+    for (e1, (damage, collisions, mut hit)) in &mut world.query::<(&Damage, &Collisions, Option<&mut HitOnce>)>() {
         for &e2 in &collisions.0 {
             if let Some(e3) = damage.exclude {
                 if e2 == e3 {
                     continue;
                 }
             }
+            // Already hit
+            if hit.as_ref().is_some_and(|hit| hit.0.contains(&e2)) {
+                continue;
+            }
             // Ignore inactive abilities(ie. shields)
             if let Ok(ability) = world.get::<&Ability>(e2) {
                 if !ability.active {
@@ -135,9 +142,19 @@ pub fn impact_and_damage(world: &mut World, socket: &Socket) {
             let Ok(mut health) = world.get::<&mut Health>(e2) else {
                 continue;
             };
+            if let Some(hit) = hit.as_deref_mut() {
+                hit.0.push(e2);
+            }
             if cfg!(server) {
-                // Inflict damage
-                health.now = (health.now - damage.amount).max(0.0);
+                // Inflict damage, boosted or not
+                let boost = match damage.source {
+                    DamageSource::Player(owner) => world
+                        .get::<&Boosts>(owner)
+                        .map(|b| b.damage_factor())
+                        .unwrap_or(1.0),
+                    DamageSource::Environment(_) => 1.0,
+                };
+                health.damage(damage.amount * boost, damage.source);
                 // Tell clients
                 socket.broadcast(&Packet::EntityHealth(e2, health.now));
             }
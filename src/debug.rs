@@ -0,0 +1,178 @@
+//! Physics debug overlay, drawn on top of everything else when
+//! toggled on. Most physics only runs on the server, which records lines
+//! for, and sends them to, clients with the overlay on. Only debug builds
+//! of the server do, so clients can't make a release one flood the
+//! network.
+
+use std::cell::{ Cell, RefCell };
+
+use hecs::World;
+
+use crate::{
+    platform::{ Canvas, Gamepad, Socket, Connection },
+    physics::{ Collider, Layers },
+    network::Packet,
+    transform::Transform,
+    math::{ Vec2, Rot2 },
+};
+
+/// A line segment of the overlay, in world space.
+///
+/// SAFETY:
+/// This type is passed directly to `platform/`.
+/// For Typescript binding simplicity, every field should be
+/// aligned to 4 bytes(ie. `u32`, `f32`).
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct Line {
+    pub from: Vec2<f32>,
+    pub to: Vec2<f32>,
+    pub color: Color,
+}
+
+/// What a [Line] represents, colored by the renderer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum Color {
+    /// Outline of a [FixedBody](crate::physics::FixedBody)
+    Fixed,
+    /// Outline of a [KinematicBody](crate::physics::KinematicBody)
+    Kinematic,
+    /// Outline of a [Trigger](crate::physics::Trigger)
+    Trigger,
+    /// Outline of any other collider
+    Other,
+    /// Contact normal
+    Contact,
+    /// Raycast
+    Ray,
+}
+
+/// Most lines the server sends each watching client per frame, past
+/// which the rest of the frame's lines are dropped.
+const MAX_SENT_LINES: usize = 256;
+
+thread_local! {
+    /// Whether the overlay is on, on the server if anyone has it on.
+    static ENABLED: Cell<bool> = const { Cell::new(false) };
+    /// Lines recorded this frame.
+    static LINES: RefCell<Vec<Line>> = const { RefCell::new(Vec::new()) };
+    /// Clients with the overlay on, server only.
+    static WATCHERS: RefCell<Vec<Connection>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Is the overlay on? Recording is a no-op otherwise.
+pub fn enabled() -> bool {
+    ENABLED.with(|e| e.get())
+}
+
+/// Record a line segment.
+pub fn line(from: Vec2<f32>, to: Vec2<f32>, color: Color) {
+    if enabled() {
+        LINES.with(|lines| lines.borrow_mut().push(Line { from, to, color }));
+    }
+}
+
+/// Record a raycast from `origin` to whatever it hit at `to`.
+pub fn ray(origin: Vec2<f32>, to: Vec2<f32>) {
+    line(origin, to, Color::Ray);
+}
+
+/// Record a contact at `point` with a surface `normal`.
+pub fn contact(point: Vec2<f32>, normal: Vec2<f32>) {
+    /// Length of the normal drawn
+    const LENGTH: f32 = 20.0;
+
+    line(point, point + normal * LENGTH, Color::Contact);
+}
+
+/// Record the outline of a collider.
+pub fn shape(collider: &Collider, transform: &Transform, color: Color) {
+    if !enabled() {
+        return;
+    }
+    let rot = Rot2::new(transform.rotation);
+    for polygon in collider.outline() {
+        let points = polygon
+            .iter()
+            .map(|&p| transform.translation + rot * p)
+            .collect::<Vec<_>>();
+        for (i, &from) in points.iter().enumerate() {
+            line(from, points[(i + 1) % points.len()], color);
+        }
+    }
+}
+
+/// System that toggles the overlay on user input, and on the server
+/// keeps track of who has it on.
+pub fn toggle(gamepad: &Gamepad, socket: &Socket) {
+    if cfg!(server) {
+        WATCHERS.with(|watchers| {
+            let mut watchers = watchers.borrow_mut();
+            for &(connection, ref packet) in socket.packets() {
+                let Packet::DebugOverlay(on) = *packet else {
+                    continue;
+                };
+                watchers.retain(|&c| c != connection);
+                if on && cfg!(debug_assertions) {
+                    watchers.push(connection);
+                }
+            }
+            watchers.retain(|c| !socket.disconnections().any(|d| d == c));
+            ENABLED.with(|e| e.set(!watchers.is_empty()));
+        });
+        return;
+    }
+    if gamepad.debug() {
+        ENABLED.with(|e| e.set(!e.get()));
+        // TODO: reliable transport
+        socket.broadcast(&Packet::DebugOverlay(enabled()));
+    }
+}
+
+/// System that records every collider's outline. Clients know of every
+/// collider, so the server doesn't send these.
+pub fn draw_colliders(world: &mut World) {
+    if cfg!(server) || !enabled() {
+        return;
+    }
+    for (e, (transform, collider)) in &mut world.query::<(&Transform, &Collider)>() {
+        let Ok(entity) = world.entity(e) else {
+            continue;
+        };
+        let color = match Layers::of(entity) {
+            Layers::FIXED => Color::Fixed,
+            Layers::KINEMATIC => Color::Kinematic,
+            Layers::TRIGGER => Color::Trigger,
+            _ => Color::Other,
+        };
+        shape(collider, transform, color);
+    }
+}
+
+/// System that draws this frame's lines, along with the server's, and
+/// clears them. The server sends its lines to clients watching instead.
+pub fn flush(canvas: &Canvas, socket: &Socket) {
+    LINES.with(|lines| {
+        let mut lines = lines.borrow_mut();
+        if cfg!(server) {
+            WATCHERS.with(|watchers| {
+                for &connection in watchers.borrow().iter() {
+                    for &line in lines.iter().take(MAX_SENT_LINES) {
+                        socket.send(connection, &Packet::DebugLine(line));
+                    }
+                }
+            });
+        }
+        if cfg!(client) {
+            if enabled() {
+                lines.extend(socket.packets().filter_map(|(_, packet)| match *packet {
+                    Packet::DebugLine(line) => Some(line),
+                    _ => None,
+                }));
+            }
+            canvas.draw_debug(&lines);
+        }
+        lines.clear();
+    });
+}
@@ -1,9 +1,11 @@
-use hecs::{ Entity, EntityBuilder, World };
+use hecs::{ Entity, EntityBuilder, World, With };
+use smallvec::SmallVec;
 
 use crate::{
     render::{ Sprite, Costume },
     transform::{ Transform, Parent, LocalPosition },
-    math::vec2, bullet::TimeToLive, platform::{Time, Socket}, player::instantiate_spawn_indicator, network::Packet,
+    math::vec2, platform::{Time, Socket}, player::{ instantiate_spawn_indicator, Player }, network::Packet,
+    timescale::TimeScale, spawn,
 };
 
 /// Component for an entity's health
@@ -12,6 +14,35 @@ pub struct Health {
     pub now: f32,
     /// Hitpoints when health resets
     pub max: f32,
+    /// What dealt damage last, credited for the kill
+    pub last_hit: Option<DamageSource>,
+}
+
+/// Who or what dealt damage, reported by kill feeds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DamageSource {
+    /// A player, ie. the owner of a gun.
+    Player(Entity),
+    /// The level itself.
+    Environment(Environment),
+}
+
+/// Parts of a level dealing damage, see `level::hazard`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Environment {
+    KillZone,
+    DamageZone,
+    Crusher,
+    /// Left the level's bounds.
+    OutOfBounds,
+}
+
+impl Health {
+    /// Take `amount` hitpoints away, crediting `source`.
+    pub fn damage(&mut self, amount: f32, source: DamageSource) {
+        self.now = (self.now - amount).max(0.0);
+        self.last_hit = Some(source);
+    }
 }
 
 /// Component for entities that deal damage when come in
@@ -23,8 +54,15 @@ pub struct Damage {
     pub exclude: Option<Entity>,
     /// Whether this entity should be destroyed after?
     pub destroy: bool,
+    /// Credited for the damage
+    pub source: DamageSource,
 }
 
+/// Component for [Damage] dealers that hurt each entity at most once,
+/// remembering who they've hit.
+#[derive(Debug, Default)]
+pub struct HitOnce(pub SmallVec<[Entity; 8]>);
+
 /// Prefab for the healthbar
 pub fn gui_prefab(owner: Entity) -> EntityBuilder {
     let mut builder = EntityBuilder::new();
@@ -41,6 +79,9 @@ pub fn gui_prefab(owner: Entity) -> EntityBuilder {
     builder
 }
 
+/// Component for players waiting to respawn.
+pub struct Dead;
+
 struct RespawnTimer {
     player: Entity,
     left: f32,
@@ -62,8 +103,12 @@ pub fn respawn_players(world: &mut World, socket: &Socket, time: &Time) {
     }
     // Kill players and remove them from the map
     let mut kill = Vec::new();
-    for (e, health) in world.query_mut::<&mut Health>() {
+    for (e, health) in world.query_mut::<With<&mut Health, &Player>>() {
         if health.now <= 0.0 {
+            // Kill feed, see `render::draw_kill_feed`
+            if let Some(source) = health.last_hit.take() {
+                socket.broadcast(&Packet::PlayerKilled { victim: e, source });
+            }
             // Reset
             health.now = health.max;
             socket.broadcast(&Packet::EntityHealth(e, health.now));
@@ -71,9 +116,14 @@ pub fn respawn_players(world: &mut World, socket: &Socket, time: &Time) {
         }
     }
     for e in kill {
-        if let Ok(mut transform) = world.get::<&mut Transform>(e) {
-            transform.translation = vec2!(-2000.0, 5000.0);
-        }
+        let Ok(mut transform) = world.get::<&mut Transform>(e) else {
+            continue;
+        };
+        let position = transform.translation;
+        transform.translation = vec2!(-2000.0, 5000.0);
+        drop(transform);
+        spawn::record_death(world, position);
+        world.insert_one(e, Dead).unwrap();
         world.spawn((
             RespawnTimer {
                 left: 2.0,
@@ -83,20 +133,30 @@ pub fn respawn_players(world: &mut World, socket: &Socket, time: &Time) {
     }
     // Respawn players and put them back on the map
     let mut rm = Vec::new();
+    let mut respawned = Vec::new();
     for (e, timer) in &mut world.query::<&mut RespawnTimer>() {
-        timer.left -= time.dt() / time.scale;
+        let scale = world
+            .get::<&TimeScale>(timer.player)
+            .map(|s| s.0)
+            .unwrap_or(1.0);
+        timer.left -= time.dt() * scale;
 
         if timer.left > 0.0 {
             continue;
         }
-        let pos = vec2!(100.0, 500.0);
-        if let Ok(mut transform) = world.get::<&mut Transform>(timer.player) {
-            transform.translation = pos;
-        }
-        socket.broadcast(&Packet::PlayerRespawn(timer.player, pos));
         rm.push(e);
+        respawned.push(timer.player);
     }
     for e in rm {
         world.despawn(e).unwrap();
     }
+    for e in respawned {
+        // Alive again, so that players respawning after this one avoid it
+        let _ = world.remove_one::<Dead>(e);
+        let pos = spawn::select(world, e);
+        if let Ok(mut transform) = world.get::<&mut Transform>(e) {
+            transform.translation = pos;
+        }
+        socket.broadcast(&Packet::PlayerRespawn(e, pos));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,76 @@
+use hecs::{ World, Without };
+
+use crate::{
+    math::vec2,
+    physics::KinematicBody,
+    transform::Transform,
+    platform::Canvas,
+    health::{ Health, Dead, DamageSource, Environment },
+};
+
+use super::format::{ Bounds, Edge };
+
+/// The current level's bounds, if any.
+fn bounds(world: &World) -> Option<Bounds> {
+    world
+        .query::<&Bounds>()
+        .iter()
+        .next()
+        .map(|(_, bounds)| *bounds)
+}
+
+/// System that applies the level's [Bounds] to players and projectiles
+/// leaving it. Runs on both ends, since clients simulate projectiles.
+pub fn enforce_bounds(world: &mut World) {
+    let Some(bounds) = bounds(world) else {
+        return;
+    };
+    let edges = [(bounds.left, bounds.right), (bounds.bottom, bounds.top)];
+    let mut killed = Vec::new();
+    type Query<'a> = Without<(&'a mut Transform, &'a mut KinematicBody), &'a Dead>;
+    for (e, (transform, kb)) in world.query_mut::<Query>() {
+        for (i, (low, high)) in edges.into_iter().enumerate() {
+            let (min, max) = (bounds.min[i], bounds.max[i]);
+            let p = &mut transform.translation[i];
+            let v = &mut kb.velocity[i];
+            let (edge, inside, outward) = match *p {
+                p if p < min => (low, min, -1.0),
+                p if p > max => (high, max, 1.0),
+                _ => continue,
+            };
+            match edge {
+                Edge::Kill => killed.push(e),
+                Edge::Wrap => *p -= outward * (max - min),
+                Edge::Wall => {
+                    *p = inside;
+                    if *v * outward > 0.0 {
+                        *v = 0.0;
+                    }
+                },
+            }
+        }
+    }
+    for e in killed {
+        let Ok(mut health) = world.get::<&mut Health>(e) else {
+            // Projectiles just vanish
+            let _ = world.despawn(e);
+            continue;
+        };
+        if cfg!(server) && health.now > 0.0 {
+            let amount = health.now;
+            health.damage(amount, DamageSource::Environment(Environment::OutOfBounds));
+            log::info!("{e:?} left the level.");
+        }
+    }
+}
+
+/// System that shows the level's bounds as an outline. The view is fixed,
+/// so there is no camera to clamp to them yet.
+pub fn draw_bounds(world: &mut World, canvas: &Canvas) {
+    if cfg!(server) {
+        return;
+    }
+    if let Some(Bounds { min, max, .. }) = bounds(world) {
+        canvas.set_bounds(vec2!(min[0], min[1]), vec2!(max[0], max[1]));
+    }
+}
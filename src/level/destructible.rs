@@ -0,0 +1,136 @@
+use hecs::{ World, Entity };
+
+use crate::{
+    physics::Collider,
+    render::{ Sprite, Costume, Visibility },
+    transform::Transform,
+    platform::{ Socket, Time },
+    network::Packet,
+    health::Health,
+    bullet::TimeToLive,
+    timescale::TimeScale,
+};
+
+/// Component for platforms that break once their [Health] runs out. A
+/// broken platform is hidden without its collider rather than despawned,
+/// so that it can come back.
+#[derive(Debug, Clone)]
+pub struct Destructible {
+    /// Seconds before coming back after breaking, if ever.
+    pub respawn: Option<f32>,
+    /// Width, for the cosmetic debris shown on clients.
+    pub width: f32,
+}
+
+/// Component for broken [Destructible]s, holding on to what they need
+/// to come back.
+#[derive(Debug)]
+struct Broken {
+    collider: Option<Collider>,
+    /// Seconds before coming back, if ever.
+    time_left: Option<f32>,
+}
+
+/// Make the platform `e` destructible.
+pub(super) fn destructible(world: &mut World, e: Entity, hitpoints: f32, respawn: Option<f32>, width: f32) {
+    world.insert(e, (
+        Destructible { respawn, width },
+        Health {
+            now: hitpoints,
+            max: hitpoints,
+            last_hit: None,
+        },
+    )).unwrap();
+}
+
+/// Take `e` out of the level by hiding it and removing its collider.
+fn break_apart(world: &mut World, e: Entity, time_left: Option<f32>) {
+    let collider = world.remove_one::<Collider>(e).ok();
+    let _ = world.insert_one(e, Broken { collider, time_left });
+    if let Ok(mut sprite) = world.get::<&mut Sprite>(e) {
+        sprite.visibility = Visibility::Hidden;
+    }
+}
+
+/// Put `e` back into the level, good as new.
+fn restore(world: &mut World, e: Entity) {
+    let Ok(broken) = world.remove_one::<Broken>(e) else {
+        return;
+    };
+    if let Some(collider) = broken.collider {
+        world.insert_one(e, collider).unwrap();
+    }
+    if let Ok(mut health) = world.get::<&mut Health>(e) {
+        health.now = health.max;
+        health.last_hit = None;
+    }
+    if let Ok(mut sprite) = world.get::<&mut Sprite>(e) {
+        sprite.visibility = Visibility::Shown;
+    }
+}
+
+/// System that breaks [Destructible]s out of health and brings them
+/// back after a while.
+pub fn destructibles(world: &mut World, socket: &Socket, time: &Time) {
+    if cfg!(client) {
+        for (_, packet) in socket.packets() {
+            match *packet {
+                Packet::TerrainBroken(e) => {
+                    if matches!(world.satisfies::<&Broken>(e), Ok(true)) {
+                        continue;
+                    }
+                    let debris = world
+                        .query_one_mut::<(&Transform, &Destructible)>(e)
+                        .map(|(t, d)| (t.translation, d.width));
+                    if let Ok((position, width)) = debris {
+                        world.spawn((
+                            Sprite::new(Costume::Debris { position, width }),
+                            TimeToLive::Frames(60),
+                        ));
+                    }
+                    break_apart(world, e, None);
+                },
+                Packet::TerrainRestored(e) => restore(world, e),
+                _ => {},
+            }
+        }
+        return;
+    }
+    // Break
+    let mut broke = Vec::new();
+    for (e, (destructible, health)) in world.query_mut::<(&Destructible, &Health)>() {
+        if health.now <= 0.0 {
+            broke.push((e, destructible.respawn));
+        }
+    }
+    for (e, respawn) in broke {
+        // Already broken ones have no collider to get hit with
+        if matches!(world.satisfies::<&Broken>(e), Ok(true)) {
+            continue;
+        }
+        break_apart(world, e, respawn);
+        socket.broadcast(&Packet::TerrainBroken(e));
+    }
+    // Respawn
+    let mut restored = Vec::new();
+    for (e, (broken, scale)) in world.query_mut::<(&mut Broken, Option<&TimeScale>)>() {
+        let Some(time_left) = &mut broken.time_left else {
+            continue;
+        };
+        *time_left -= time.dt() * scale.map(|s| s.0).unwrap_or(1.0);
+        if *time_left <= 0.0 {
+            restored.push(e);
+        }
+    }
+    for e in restored {
+        restore(world, e);
+        socket.broadcast(&Packet::TerrainRestored(e));
+    }
+    // Newcomers need to know what's already broken
+    // TODO: reliable transport
+    for &connection in socket.connections() {
+        for (e, _) in world.query_mut::<&Broken>() {
+            socket.send(connection, &Packet::TerrainBroken(e));
+        }
+    }
+}
@@ -0,0 +1,562 @@
+//! On-disk level format. Levels are TOML files in `assets/levels/`,
+//! see `arena.toml` for an example of every feature.
+
+use std::fmt;
+
+use serde::Deserialize;
+
+use crate::{
+    math::{ Vec2, vec2 },
+    physics::{ Collider, SurfaceKind },
+    pickup::PickupKind,
+};
+
+/// Most entities a level can be made of, see [Level::entities].
+pub const MAX_ENTITIES: usize = 256;
+
+/// A point in world space, `[x, y]`.
+pub type Point = [f32; 2];
+
+/// Convert a [Point] to a vector.
+pub fn point([x, y]: Point) -> Vec2<f32> {
+    vec2!(x, y)
+}
+
+/// A whole level, as described by a level file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Level {
+    pub metadata: Metadata,
+    pub bounds: Bounds,
+    #[serde(default)]
+    pub spawns: Vec<SpawnPoint>,
+    #[serde(default)]
+    pub platforms: Vec<Platform>,
+    #[serde(default)]
+    pub solids: Vec<Solid>,
+    #[serde(default)]
+    pub hazards: Vec<Hazard>,
+    #[serde(default)]
+    pub jump_pads: Vec<JumpPad>,
+    #[serde(default)]
+    pub teleporters: Vec<Teleporters>,
+    #[serde(default)]
+    pub pickups: Vec<Pickup>,
+    #[serde(default)]
+    pub backgrounds: Vec<Background>,
+}
+
+/// Information about a level that doesn't affect gameplay.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Metadata {
+    pub name: String,
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// Rectangle the level is played in, and what happens to bodies
+/// crossing each of its edges. Falling out kills by default, other edges
+/// are walls.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Bounds {
+    pub min: Point,
+    pub max: Point,
+    #[serde(default)]
+    pub left: Edge,
+    #[serde(default)]
+    pub right: Edge,
+    #[serde(default)]
+    pub top: Edge,
+    #[serde(default = "Edge::kill")]
+    pub bottom: Edge,
+}
+
+/// What happens to bodies crossing an edge of the [Bounds].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Edge {
+    /// Players die, projectiles are destroyed.
+    Kill,
+    /// Come back in from the opposite edge.
+    Wrap,
+    /// Can't go through.
+    #[default]
+    Wall,
+}
+
+impl Edge {
+    fn kill() -> Self {
+        Edge::Kill
+    }
+}
+
+/// Where players can (re)spawn.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpawnPoint {
+    pub position: Point,
+}
+
+/// A 20 units thick platform, centered on `position`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Platform {
+    pub position: Point,
+    pub width: f32,
+    /// Can be jumped up through and dropped down from.
+    #[serde(default)]
+    pub one_way: bool,
+    #[serde(default)]
+    pub material: SurfaceKind,
+    pub motion: Option<Motion>,
+    /// Hitpoints, if it can be destroyed.
+    pub health: Option<f32>,
+    /// Seconds before coming back once destroyed, if ever.
+    pub respawn: Option<f32>,
+}
+
+/// How a [Platform] moves, relative to its position.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum Motion {
+    /// Travel through `points` in a loop, starting at the platform.
+    Path {
+        points: Vec<Point>,
+        speed: f32,
+    },
+    /// Oscillate around the platform's position.
+    Sine {
+        amplitude: Point,
+        frequency: f32,
+    },
+}
+
+/// Level geometry of any convex shape, or concave one made of several
+/// convex `parts`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Solid {
+    pub position: Point,
+    /// Vertices relative to `position`.
+    #[serde(default)]
+    pub points: Vec<Point>,
+    /// Vertices of each part relative to `position`, instead of `points`.
+    #[serde(default)]
+    pub parts: Vec<Vec<Point>>,
+    #[serde(default)]
+    pub material: SurfaceKind,
+}
+
+impl Solid {
+    /// Collider of the solid, if `points` or every part is a convex
+    /// polygon.
+    pub fn collider(&self) -> Option<Collider> {
+        let polygon = |points: &[Point]| {
+            Collider::polygon(&points.iter().copied().map(point).collect::<Vec<_>>())
+        };
+        if self.parts.is_empty() {
+            return polygon(&self.points);
+        }
+        self.parts
+            .iter()
+            .map(|part| Some((vec2!(0.0, 0.0), polygon(part)?)))
+            .collect::<Option<Vec<_>>>()
+            .map(Collider::compound)
+    }
+}
+
+/// Areas that hurt.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum Hazard {
+    /// Instantly kills everything inside `min` to `max`.
+    Kill {
+        min: Point,
+        max: Point,
+    },
+    /// Hurts everything inside `min` to `max` by `dps` hitpoints per
+    /// second, ie. spikes or lava.
+    Damage {
+        min: Point,
+        max: Point,
+        dps: f32,
+    },
+    /// A `size` block resting at `position`, that slams `travel` units
+    /// down every `period` seconds, killing what it lands on.
+    Crusher {
+        position: Point,
+        size: Point,
+        travel: f32,
+        period: f32,
+    },
+}
+
+/// Launches players touching it.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JumpPad {
+    pub position: Point,
+    pub width: f32,
+    pub velocity: Point,
+}
+
+/// A pair of teleporters, leading to one another.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Teleporters {
+    pub a: Point,
+    pub b: Point,
+}
+
+/// Where a pickup appears, and reappears a while after being taken.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Pickup {
+    pub position: Point,
+    pub kind: PickupKind,
+    /// Seconds before reappearing once taken.
+    #[serde(default = "Pickup::respawn")]
+    pub respawn: f32,
+}
+
+impl Pickup {
+    fn respawn() -> f32 {
+        20.0
+    }
+}
+
+/// A decorative image drawn behind the level.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Background {
+    pub image: String,
+    /// How much the layer follows the camera, 0 is fixed in place.
+    #[serde(default)]
+    pub parallax: f32,
+}
+
+/// Why a level file couldn't be loaded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LevelError {
+    /// Not valid TOML, or doesn't match the format. Says where in the
+    /// file, and shows the offending line.
+    Parse(String),
+    /// Well-formed, but doesn't make sense, ie. a negative width.
+    Invalid {
+        /// Path to the offending item, ie. `platforms[2].width`.
+        at: String,
+        reason: String,
+    },
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LevelError::Parse(message) => write!(f, "malformed level: {message}"),
+            LevelError::Invalid { at, reason } => write!(f, "invalid level at `{at}`: {reason}"),
+        }
+    }
+}
+
+/// Shorthand for a [LevelError::Invalid]
+fn invalid(at: impl Into<String>, reason: impl Into<String>) -> LevelError {
+    LevelError::Invalid { at: at.into(), reason: reason.into() }
+}
+
+impl Bounds {
+    /// Is `p` inside the bounds?
+    pub fn contains(&self, p: Point) -> bool {
+        (0..2).all(|i| self.min[i] <= p[i] && p[i] <= self.max[i])
+    }
+}
+
+impl Level {
+    /// Number of entities spawned by the level.
+    pub fn entities(&self) -> usize {
+        // Bounds are one more, teleporters come in pairs
+        self.spawns.len()
+            + self.platforms.len()
+            + self.solids.len()
+            + self.hazards.len()
+            + self.jump_pads.len()
+            + self.teleporters.len() * 2
+            + self.pickups.len()
+            + 1
+    }
+
+    /// Parse and validate a level file.
+    pub fn parse(src: &str) -> Result<Self, LevelError> {
+        let level: Level = toml::from_str(src)
+            .map_err(|e| LevelError::Parse(e.to_string()))?;
+        level.validate()?;
+        Ok(level)
+    }
+
+    /// Check everything the format itself can't express.
+    pub fn validate(&self) -> Result<(), LevelError> {
+        let finite = |at: &str, p: Point| match p.iter().all(|x| x.is_finite()) {
+            true => Ok(()),
+            false => Err(invalid(at, "coordinates must be finite")),
+        };
+        let inside = |at: &str, p: Point| match self.bounds.contains(p) {
+            true => Ok(()),
+            false => Err(invalid(at, format!("{p:?} is outside of the level bounds"))),
+        };
+
+        if self.metadata.name.trim().is_empty() {
+            return Err(invalid("metadata.name", "levels must be named"));
+        }
+        finite("bounds.min", self.bounds.min)?;
+        finite("bounds.max", self.bounds.max)?;
+        if (0..2).any(|i| self.bounds.min[i] >= self.bounds.max[i]) {
+            return Err(invalid("bounds", "`min` must be below and left of `max`"));
+        }
+        let Bounds { left, right, top, bottom, .. } = self.bounds;
+        if (left == Edge::Wrap) != (right == Edge::Wrap) || (top == Edge::Wrap) != (bottom == Edge::Wrap) {
+            return Err(invalid("bounds", "wrapping edges come in opposite pairs"));
+        }
+        if self.entities() > MAX_ENTITIES {
+            return Err(invalid("level", format!("levels can't have more than {MAX_ENTITIES} entities")));
+        }
+        if self.spawns.is_empty() {
+            return Err(invalid("spawns", "at least one spawn point is required"));
+        }
+        for (i, spawn) in self.spawns.iter().enumerate() {
+            let at = format!("spawns[{i}].position");
+            finite(&at, spawn.position)?;
+            inside(&at, spawn.position)?;
+        }
+        for (i, platform) in self.platforms.iter().enumerate() {
+            let at = format!("platforms[{i}]");
+            finite(&format!("{at}.position"), platform.position)?;
+            inside(&format!("{at}.position"), platform.position)?;
+            if !(platform.width > 0.0 && platform.width.is_finite()) {
+                return Err(invalid(format!("{at}.width"), "must be positive"));
+            }
+            if let Some(health) = platform.health {
+                if !(health > 0.0 && health.is_finite()) {
+                    return Err(invalid(format!("{at}.health"), "must be positive"));
+                }
+            }
+            match platform.respawn {
+                Some(_) if platform.health.is_none() => {
+                    return Err(invalid(format!("{at}.respawn"), "only destructible platforms respawn"));
+                },
+                Some(respawn) if !(respawn >= 0.0 && respawn.is_finite()) => {
+                    return Err(invalid(format!("{at}.respawn"), "can't be negative"));
+                },
+                _ => {},
+            }
+            match &platform.motion {
+                Some(Motion::Path { points, speed }) => {
+                    if points.len() < 2 {
+                        return Err(invalid(format!("{at}.motion.points"), "paths need at least 2 points"));
+                    }
+                    for (j, &p) in points.iter().enumerate() {
+                        finite(&format!("{at}.motion.points[{j}]"), p)?;
+                        inside(&format!("{at}.motion.points[{j}]"), p)?;
+                    }
+                    if !(*speed > 0.0 && speed.is_finite()) {
+                        return Err(invalid(format!("{at}.motion.speed"), "must be positive"));
+                    }
+                },
+                Some(Motion::Sine { amplitude, frequency }) => {
+                    finite(&format!("{at}.motion.amplitude"), *amplitude)?;
+                    if !(*frequency >= 0.0 && frequency.is_finite()) {
+                        return Err(invalid(format!("{at}.motion.frequency"), "can't be negative"));
+                    }
+                },
+                None => {},
+            }
+        }
+        for (i, solid) in self.solids.iter().enumerate() {
+            let at = format!("solids[{i}]");
+            finite(&format!("{at}.position"), solid.position)?;
+            inside(&format!("{at}.position"), solid.position)?;
+            if !solid.points.is_empty() && !solid.parts.is_empty() {
+                return Err(invalid(&at, "either `points` or `parts` can be given, not both"));
+            }
+            if solid.collider().is_none() {
+                let field = if solid.parts.is_empty() { "points" } else { "parts" };
+                return Err(invalid(format!("{at}.{field}"), "must form convex polygons"));
+            }
+        }
+        for (i, hazard) in self.hazards.iter().enumerate() {
+            let at = format!("hazards[{i}]");
+            match hazard {
+                Hazard::Kill { min, max } | Hazard::Damage { min, max, .. } => {
+                    finite(&format!("{at}.min"), *min)?;
+                    finite(&format!("{at}.max"), *max)?;
+                    if (0..2).any(|i| min[i] >= max[i]) {
+                        return Err(invalid(at, "`min` must be below and left of `max`"));
+                    }
+                },
+                Hazard::Crusher { position, size, travel, period } => {
+                    finite(&format!("{at}.position"), *position)?;
+                    inside(&format!("{at}.position"), *position)?;
+                    if !size.iter().all(|x| *x > 0.0 && x.is_finite()) {
+                        return Err(invalid(format!("{at}.size"), "must be positive"));
+                    }
+                    if !(*travel > 0.0 && travel.is_finite()) {
+                        return Err(invalid(format!("{at}.travel"), "must be positive"));
+                    }
+                    if !(*period > 0.0 && period.is_finite()) {
+                        return Err(invalid(format!("{at}.period"), "must be positive"));
+                    }
+                },
+            }
+            if let Hazard::Damage { dps, .. } = hazard {
+                if !(*dps > 0.0 && dps.is_finite()) {
+                    return Err(invalid(format!("{at}.dps"), "must be positive"));
+                }
+            }
+        }
+        for (i, pad) in self.jump_pads.iter().enumerate() {
+            let at = format!("jump_pads[{i}]");
+            finite(&format!("{at}.position"), pad.position)?;
+            inside(&format!("{at}.position"), pad.position)?;
+            finite(&format!("{at}.velocity"), pad.velocity)?;
+            if !(pad.width > 0.0 && pad.width.is_finite()) {
+                return Err(invalid(format!("{at}.width"), "must be positive"));
+            }
+        }
+        for (i, pair) in self.teleporters.iter().enumerate() {
+            let at = format!("teleporters[{i}]");
+            for (end, p) in [("a", pair.a), ("b", pair.b)] {
+                finite(&format!("{at}.{end}"), p)?;
+                inside(&format!("{at}.{end}"), p)?;
+            }
+            if pair.a == pair.b {
+                return Err(invalid(at, "both ends are in the same place"));
+            }
+        }
+        for (i, pickup) in self.pickups.iter().enumerate() {
+            let at = format!("pickups[{i}]");
+            finite(&format!("{at}.position"), pickup.position)?;
+            inside(&format!("{at}.position"), pickup.position)?;
+            if !(pickup.respawn >= 0.0 && pickup.respawn.is_finite()) {
+                return Err(invalid(format!("{at}.respawn"), "can't be negative"));
+            }
+        }
+        for (i, background) in self.backgrounds.iter().enumerate() {
+            let at = format!("backgrounds[{i}]");
+            if background.image.trim().is_empty() {
+                return Err(invalid(format!("{at}.image"), "can't be empty"));
+            }
+            if !(0.0..=1.0).contains(&background.parallax) {
+                return Err(invalid(format!("{at}.parallax"), "must be between 0 and 1"));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::level::LEVELS;
+
+    const MINIMAL: &str = r#"
+        [metadata]
+        name = "Test"
+        [bounds]
+        min = [0, 0]
+        max = [100, 100]
+        [[spawns]]
+        position = [50, 50]
+    "#;
+
+    #[test]
+    fn embedded_levels_are_valid() {
+        for src in LEVELS {
+            if let Err(e) = Level::parse(src) {
+                panic!("{e}");
+            }
+        }
+    }
+
+    #[test]
+    fn reports_where_a_level_is_invalid() {
+        let src = format!("{MINIMAL}
+            [[platforms]]
+            position = [10, 10]
+            width = -5
+        ");
+        assert_eq!(
+            Level::parse(&src).unwrap_err().to_string(),
+            "invalid level at `platforms[0].width`: must be positive",
+        );
+        let src = MINIMAL.replace("[50, 50]", "[500, 50]");
+        assert!(matches!(
+            Level::parse(&src),
+            Err(LevelError::Invalid { at, .. }) if at == "spawns[0].position"
+        ));
+    }
+
+    #[test]
+    fn solids_can_be_made_of_parts() {
+        let src = format!("{MINIMAL}
+            [[solids]]
+            position = [50, 10]
+            parts = [
+                [[-20, 0], [20, 0], [20, 10], [-20, 10]],
+                [[-20, 10], [-10, 10], [-10, 30], [-20, 30]],
+            ]
+        ");
+        let level = Level::parse(&src).unwrap();
+        assert!(matches!(level.solids[0].collider(), Some(Collider::Compound(_))));
+        // Empty part
+        let src = src.replace("[[-20, 10], [-10, 10], [-10, 30], [-20, 30]]", "[]");
+        assert!(matches!(
+            Level::parse(&src),
+            Err(LevelError::Invalid { at, .. }) if at == "solids[0].parts"
+        ));
+    }
+
+    #[test]
+    fn rejects_concave_solids() {
+        let src = format!("{MINIMAL}
+            [[solids]]
+            position = [50, 10]
+            points = [[-20, 0], [20, 0], [20, 30], [10, 30], [10, 10], [-20, 10]]
+        ");
+        assert!(matches!(
+            Level::parse(&src),
+            Err(LevelError::Invalid { at, .. }) if at == "solids[0].points"
+        ));
+        // Same shape, clockwise
+        let src = src.replace(
+            "[[-20, 0], [20, 0], [20, 30], [10, 30], [10, 10], [-20, 10]]",
+            "[[-20, 10], [10, 10], [10, 30], [20, 30], [20, 0], [-20, 0]]",
+        );
+        assert!(matches!(
+            Level::parse(&src),
+            Err(LevelError::Invalid { at, .. }) if at == "solids[0].points"
+        ));
+        // Convex either way around
+        let src = src.replace(
+            "[[-20, 10], [10, 10], [10, 30], [20, 30], [20, 0], [-20, 0]]",
+            "[[-20, 10], [20, 10], [20, 0], [-20, 0]]",
+        );
+        let level = Level::parse(&src).unwrap();
+        assert!(matches!(level.solids[0].collider(), Some(Collider::Polygon(_))));
+    }
+
+    #[test]
+    fn rejects_unknown_fields() {
+        let src = format!("{MINIMAL}
+            [[platforms]]
+            position = [10, 10]
+            widht = 5
+        ");
+        let Err(LevelError::Parse(message)) = Level::parse(&src) else {
+            panic!("unknown field accepted");
+        };
+        assert!(message.contains("line 12"), "{message}");
+        assert!(message.contains("widht"), "{message}");
+    }
+}
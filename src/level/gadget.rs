@@ -0,0 +1,159 @@
+use hecs::{ World, Entity };
+
+use crate::{
+    math::Vec2,
+    physics::{ Collider, KinematicBody, Trigger, TriggerEvent },
+    render::{ Sprite, Costume },
+    transform::Transform,
+    platform::{ Socket, Time },
+    network::Packet,
+    player::Player,
+    bullet::TimeToLive,
+    timescale::TimeScale,
+};
+
+/// Height of a jump pad's trigger.
+const PAD_HEIGHT: f32 = 10.0;
+/// Size of a teleporter's trigger.
+const TELEPORTER_SIZE: f32 = 60.0;
+/// Seconds before a body can be teleported again.
+const TELEPORT_COOLDOWN: f32 = 1.0;
+
+/// Component for [Trigger]s launching players that touch them.
+#[derive(Debug, Clone)]
+pub struct JumpPad {
+    /// Velocity players are launched at.
+    pub velocity: Vec2<f32>,
+}
+
+/// Component for [Trigger]s moving players that enter them to their
+/// `pair`, and vice versa.
+#[derive(Debug, Clone)]
+pub struct Teleporter {
+    pub pair: Entity,
+}
+
+/// Component for bodies that were just teleported, and can't be again
+/// for a while.
+#[derive(Debug, Clone)]
+struct TeleportCooldown(f32);
+
+/// A `width` wide jump pad at `pos`, launching at `velocity`.
+pub(super) fn jump_pad(world: &mut World, e: Entity, pos: Vec2<f32>, width: f32, velocity: Vec2<f32>) {
+    world.spawn_at(e, (
+        Collider::rect(width, PAD_HEIGHT),
+        Trigger::default(),
+        JumpPad { velocity },
+        Sprite::new(Costume::JumpPad {
+            position: pos,
+            width,
+        }),
+        Transform {
+            translation: pos,
+            rotation: 0.0,
+        },
+    ));
+}
+
+/// A pair of teleporters at `a` and `b`, leading to one another.
+pub(super) fn teleporter_pair(world: &mut World, (e1, a): (Entity, Vec2<f32>), (e2, b): (Entity, Vec2<f32>)) {
+    for (e, pos, pair) in [(e1, a, e2), (e2, b, e1)] {
+        world.spawn_at(e, (
+            Collider::rect(TELEPORTER_SIZE, TELEPORTER_SIZE),
+            Trigger::default(),
+            Teleporter { pair },
+            Sprite::new(Costume::Teleporter { position: pos }),
+            Transform {
+                translation: pos,
+                rotation: 0.0,
+            },
+        ));
+    }
+}
+
+/// Spawn the short-lived effect sprites the server asks for, of the
+/// costumes passing `filter`.
+fn spawn_effects(world: &mut World, socket: &Socket, filter: fn(&Costume) -> bool) {
+    for (_, packet) in socket.packets() {
+        let Packet::EffectSpawn(costume) = packet else {
+            continue;
+        };
+        if !filter(costume) {
+            continue;
+        }
+        world.spawn((
+            Sprite::new(costume.clone()),
+            TimeToLive::Frames(30),
+        ));
+    }
+}
+
+/// System that launches players touching a [JumpPad]
+pub fn jump_pads(world: &mut World, socket: &Socket) {
+    if cfg!(client) {
+        spawn_effects(world, socket, |c| matches!(c, Costume::Launch { .. }));
+        return;
+    }
+    for (_, (trigger, pad, transform)) in &mut world.query::<(&Trigger, &JumpPad, &Transform)>() {
+        for &(e, event) in trigger.events() {
+            if event != TriggerEvent::Enter || !matches!(world.satisfies::<&Player>(e), Ok(true)) {
+                continue;
+            }
+            let Ok(mut kb) = world.get::<&mut KinematicBody>(e) else {
+                continue;
+            };
+            kb.velocity = pad.velocity;
+            socket.broadcast(&Packet::EffectSpawn(Costume::Launch {
+                position: transform.translation,
+            }));
+        }
+    }
+}
+
+/// System that moves players entering a [Teleporter] to its pair
+pub fn teleporters(world: &mut World, socket: &Socket, time: &Time) {
+    if cfg!(client) {
+        spawn_effects(world, socket, |c| matches!(c, Costume::Warp { .. }));
+        return;
+    }
+    // Cooldown
+    let mut ready = Vec::new();
+    for (e, (cooldown, scale)) in world.query_mut::<(&mut TeleportCooldown, Option<&TimeScale>)>() {
+        cooldown.0 -= time.dt() * scale.map(|s| s.0).unwrap_or(1.0);
+        if cooldown.0 <= 0.0 {
+            ready.push(e);
+        }
+    }
+    for e in ready {
+        world.remove_one::<TeleportCooldown>(e).unwrap();
+    }
+    // Teleport
+    let mut teleports = Vec::new();
+    for (_, (trigger, teleporter, transform)) in &mut world.query::<(&Trigger, &Teleporter, &Transform)>() {
+        let Ok(exit) = world.get::<&Transform>(teleporter.pair) else {
+            continue;
+        };
+        for &(e, event) in trigger.events() {
+            if event != TriggerEvent::Enter || !matches!(world.satisfies::<&Player>(e), Ok(true)) {
+                continue;
+            }
+            if matches!(world.satisfies::<&TeleportCooldown>(e), Ok(true)) {
+                continue;
+            }
+            teleports.push((e, transform.translation, exit.translation));
+        }
+    }
+    for (e, entrance, exit) in teleports {
+        // Several teleporters could've been entered at once
+        if matches!(world.satisfies::<&TeleportCooldown>(e), Ok(true)) {
+            continue;
+        }
+        if let Ok(mut transform) = world.get::<&mut Transform>(e) {
+            transform.translation = exit;
+        }
+        world.insert_one(e, TeleportCooldown(TELEPORT_COOLDOWN)).unwrap();
+        for position in [entrance, exit] {
+            socket.broadcast(&Packet::EffectSpawn(Costume::Warp { position }));
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! Seeded procedural arenas.
+//!
+//! Platforms are added one at a time, each within a running jump of one
+//! already placed, so every platform can be reached from every other.
+
+use fastrand::Rng;
+
+use crate::{
+    physics::SurfaceKind,
+    player::{ jump_arc, jump_reach },
+    pickup::PickupKind,
+};
+
+use super::format::{
+    Level, Metadata, Bounds, Edge, SpawnPoint, Platform, Pickup, Point,
+};
+
+/// Number of platforms in an arena.
+const PLATFORMS: usize = 14;
+/// Attempts at placing a platform before giving up on it.
+const ATTEMPTS: usize = 50;
+/// Number of spawn points, if there are enough platforms.
+const SPAWNS: usize = 4;
+/// Area platforms are placed in, from `[0, 0]`.
+const EXTENT: Point = [2400.0, 1200.0];
+const MIN_WIDTH: f32 = 120.0;
+const MAX_WIDTH: f32 = 400.0;
+/// Smallest horizontal gap between platforms.
+const MIN_GAP: f32 = 40.0;
+/// Vertical space kept between platforms above one another.
+const CLEARANCE: f32 = 150.0;
+/// Deepest drop from one platform to the next.
+const MAX_DROP: f32 = 300.0;
+/// Fraction of the player's jump used, leaves room for error.
+const SAFETY: f32 = 0.8;
+/// Space between the platforms and the level bounds.
+const MARGIN: f32 = 500.0;
+/// How high above its platform a spawn point is.
+const SPAWN_HEIGHT: f32 = 60.0;
+/// One of each, on platforms without a spawn point.
+const PICKUPS: [PickupKind; 4] = [
+    PickupKind::Health,
+    PickupKind::DamageBoost,
+    PickupKind::SpeedBoost,
+    PickupKind::CooldownReset,
+];
+/// How high above its platform a pickup is.
+const PICKUP_HEIGHT: f32 = 40.0;
+/// Seconds before a pickup comes back.
+const PICKUP_RESPAWN: f32 = 20.0;
+
+/// Generate an arena, the same `seed` always gives the same arena.
+pub fn arena(seed: u64) -> Level {
+    let rng = Rng::with_seed(seed);
+    let apex = jump_arc()
+        .map(|p| p.y)
+        .fold(0.0, f32::max);
+
+    let mut platforms = vec![Platform {
+        position: [EXTENT[0] / 2.0, EXTENT[1] / 10.0],
+        width: MAX_WIDTH * 1.5,
+        one_way: false,
+        material: SurfaceKind::Normal,
+        motion: None,
+        health: None,
+        respawn: None,
+    }];
+    for _ in 1..PLATFORMS {
+        for _ in 0..ATTEMPTS {
+            let from = &platforms[rng.usize(..platforms.len())];
+            // Platforms below must be climbed back up from, so gaps are
+            // sized for jumping up whichever way the platform is
+            let dy = rng.f32() * (apex * SAFETY + MAX_DROP) - MAX_DROP;
+            let Some(reach) = jump_reach(dy.abs()) else {
+                continue;
+            };
+            let gap = MIN_GAP + rng.f32() * (reach * SAFETY - MIN_GAP).max(0.0);
+            let width = MIN_WIDTH + rng.f32() * (MAX_WIDTH - MIN_WIDTH);
+            let side = if rng.bool() { 1.0 } else { -1.0 };
+            let position = [
+                from.position[0] + side * (from.width / 2.0 + gap + width / 2.0),
+                from.position[1] + dy,
+            ];
+            let candidate = Platform {
+                position,
+                width,
+                one_way: rng.f32() < 0.3,
+                material: match rng.f32() < 0.1 {
+                    true => SurfaceKind::Ice,
+                    false => SurfaceKind::Normal,
+                },
+                motion: None,
+                health: None,
+                respawn: None,
+            };
+            if fits(&candidate, &platforms) {
+                platforms.push(candidate);
+                break;
+            }
+        }
+    }
+
+    // Spread spawn points over random platforms
+    let mut order = (0..platforms.len()).collect::<Vec<_>>();
+    rng.shuffle(&mut order);
+    let spawns = order
+        .iter()
+        .take(SPAWNS)
+        .map(|&i| SpawnPoint {
+            position: [
+                platforms[i].position[0],
+                platforms[i].position[1] + SPAWN_HEIGHT,
+            ],
+        })
+        .collect::<Vec<_>>();
+    let pickups = order
+        .iter()
+        .skip(SPAWNS)
+        .zip(PICKUPS)
+        .map(|(&i, kind)| Pickup {
+            position: [
+                platforms[i].position[0],
+                platforms[i].position[1] + PICKUP_HEIGHT,
+            ],
+            kind,
+            respawn: PICKUP_RESPAWN,
+        })
+        .collect::<Vec<_>>();
+
+    let (min, max) = platforms
+        .iter()
+        .fold(([f32::MAX; 2], [f32::MIN; 2]), |(min, max), p| (
+            [min[0].min(p.position[0] - p.width / 2.0), min[1].min(p.position[1])],
+            [max[0].max(p.position[0] + p.width / 2.0), max[1].max(p.position[1])],
+        ));
+    let bounds = Bounds {
+        min: [min[0] - MARGIN, min[1] - MARGIN],
+        max: [max[0] + MARGIN, max[1] + MARGIN * 2.0],
+        // Around the sides, into the void underneath
+        left: Edge::Wrap,
+        right: Edge::Wrap,
+        top: Edge::Wall,
+        bottom: Edge::Kill,
+    };
+
+    Level {
+        metadata: Metadata {
+            name: format!("Arena #{seed}"),
+            author: "generator".to_owned(),
+            description: String::new(),
+        },
+        bounds,
+        spawns,
+        platforms,
+        solids: Vec::new(),
+        hazards: Vec::new(),
+        jump_pads: Vec::new(),
+        teleporters: Vec::new(),
+        pickups,
+        backgrounds: Vec::new(),
+    }
+}
+
+/// Is there room for `candidate` among `platforms`, within the arena?
+fn fits(candidate: &Platform, platforms: &[Platform]) -> bool {
+    let [x, y] = candidate.position;
+    let half = candidate.width / 2.0;
+    if x - half < 0.0 || x + half > EXTENT[0] || y < 0.0 || y > EXTENT[1] {
+        return false;
+    }
+    platforms.iter().all(|p| {
+        let apart = (p.position[0] - x).abs() >= p.width / 2.0 + half + MIN_GAP;
+        apart || (p.position[1] - y).abs() >= CLEARANCE
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arenas_are_valid_and_seeded() {
+        for seed in 0..20 {
+            let level = arena(seed);
+            if let Err(e) = level.validate() {
+                panic!("seed {seed}: {e}");
+            }
+            assert!(level.platforms.len() > PLATFORMS / 2);
+            let again = arena(seed);
+            let positions = |l: &Level| l.platforms
+                .iter()
+                .map(|p| p.position)
+                .collect::<Vec<_>>();
+            assert_eq!(positions(&level), positions(&again));
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use hecs::{ World, Entity, With };
+
+use crate::{
+    math::{ Vec2, vec2 },
+    physics::{ self, Collider, FixedBody, Trigger, TriggerEvent, QueryFilter, Layers },
+    render::{ Sprite, Costume },
+    transform::{ Transform, NetworkPosition },
+    platform::{ Socket, Time },
+    network::Packet,
+    health::{ Health, DamageSource, Environment },
+    timescale::TimeScale,
+};
+
+/// Distance below a descending [Crusher] in which players are crushed.
+const CRUSH_MARGIN: f32 = 10.0;
+
+/// Component for [Trigger]s that instantly kill whatever enters them.
+#[derive(Debug, Default, Clone)]
+pub struct KillZone;
+
+/// Component for [Trigger]s that hurt whatever stays inside them,
+/// ie. spikes or lava.
+#[derive(Debug, Clone)]
+pub struct DamageZone {
+    /// Hitpoints lost per second inside.
+    pub dps: f32,
+}
+
+/// Component for solid blocks that periodically slam down, killing
+/// whatever they land on.
+#[derive(Debug, Clone)]
+pub struct Crusher {
+    /// Resting position, at the top.
+    pub top: Vec2<f32>,
+    /// Distance travelled down.
+    pub travel: f32,
+    /// Seconds for a whole cycle.
+    pub period: f32,
+    /// Seconds elapsed since the start of the cycle.
+    pub time: f32,
+}
+
+impl Crusher {
+    /// Distance below the top at `time`, and whether it's slamming down.
+    fn offset(&self) -> (f32, bool) {
+        let phase = (self.time / self.period).fract();
+        match phase {
+            // Rest, up
+            p if p < 0.5 => (0.0, false),
+            // Slam
+            p if p < 0.6 => (self.travel * (p - 0.5) / 0.1, true),
+            // Rest, down
+            p if p < 0.7 => (self.travel, false),
+            // Rise
+            p => (self.travel * (1.0 - (p - 0.7) / 0.3), false),
+        }
+    }
+}
+
+/// An area spanning `min` to `max` that kills everything inside it.
+pub(super) fn kill_zone(world: &mut World, e: Entity, min: Vec2<f32>, max: Vec2<f32>) {
+    let size = max - min;
+    world.spawn_at(e, (
+        Collider::rect(size.x, size.y),
+        Trigger::default(),
+        KillZone,
+        Transform {
+            translation: min + size / 2.0,
+            rotation: 0.0,
+        },
+    ));
+}
+
+/// An area spanning `min` to `max` that hurts everything inside it.
+pub(super) fn damage_zone(world: &mut World, e: Entity, min: Vec2<f32>, max: Vec2<f32>, dps: f32) {
+    let size = max - min;
+    let center = min + size / 2.0;
+    world.spawn_at(e, (
+        Collider::rect(size.x, size.y),
+        Trigger::default(),
+        DamageZone { dps },
+        Sprite::new(Costume::Hazard {
+            position: center,
+            size,
+        }),
+        Transform {
+            translation: center,
+            rotation: 0.0,
+        },
+    ));
+}
+
+/// A `size` block resting at `top`, slamming `travel` down every `period`.
+pub(super) fn crusher(world: &mut World, e: Entity, top: Vec2<f32>, size: Vec2<f32>, travel: f32, period: f32) {
+    let collider = Collider::rect(size.x, size.y);
+    let transform = Transform {
+        translation: top,
+        rotation: 0.0,
+    };
+    world.spawn_at(e, (
+        Sprite::outline(&collider, &transform),
+        collider,
+        FixedBody::default(),
+        Crusher {
+            top,
+            travel,
+            period,
+            time: 0.0,
+        },
+        transform,
+        NetworkPosition,
+        TimeScale::default(),
+    ));
+}
+
+/// System that instantly kills entities inside a [KillZone]
+pub fn kill_zones(world: &mut World) {
+    if cfg!(client) {
+        return;
+    }
+    for (_, trigger) in &mut world.query::<With<&Trigger, &KillZone>>() {
+        for &(e, event) in trigger.events() {
+            if event == TriggerEvent::Exit {
+                continue;
+            }
+            let Ok(mut health) = world.get::<&mut Health>(e) else {
+                continue;
+            };
+            if health.now > 0.0 {
+                let amount = health.now;
+                health.damage(amount, DamageSource::Environment(Environment::KillZone));
+                log::info!("{e:?} entered a kill zone.");
+            }
+        }
+    }
+}
+
+/// System that hurts entities inside a [DamageZone]
+pub fn damage_zones(world: &mut World, socket: &Socket, time: &Time) {
+    if cfg!(client) {
+        return;
+    }
+    for (_, (trigger, zone)) in &mut world.query::<(&Trigger, &DamageZone)>() {
+        for &e in trigger.overlaps() {
+            let Ok(mut health) = world.get::<&mut Health>(e) else {
+                continue;
+            };
+            let scale = world
+                .get::<&TimeScale>(e)
+                .map(|s| s.0)
+                .unwrap_or(1.0);
+            health.damage(
+                zone.dps * time.dt() * scale,
+                DamageSource::Environment(Environment::DamageZone),
+            );
+            socket.broadcast(&Packet::EntityHealth(e, health.now));
+        }
+    }
+}
+
+/// System that moves [Crusher]s and kills whatever is under them as
+/// they slam down.
+pub fn crushers(world: &mut World, time: &Time) {
+    if cfg!(client) {
+        return;
+    }
+    type Query<'a> = (
+        &'a mut Crusher,
+        &'a mut FixedBody,
+        &'a mut Transform,
+        &'a Collider,
+        Option<&'a TimeScale>,
+    );
+    let mut crushed = Vec::new();
+    for (_, (crusher, fb, transform, collider, scale)) in world.query_mut::<Query>() {
+        let scale = scale
+            .map(|s| s.0)
+            .unwrap_or(1.0);
+        crusher.time += time.dt() * scale;
+        let (offset, slamming) = crusher.offset();
+        let next = crusher.top - vec2!(0.0, offset);
+        // Velocity in real time, like moving platforms
+        fb.velocity = match time.dt() > 0.0 {
+            true => (next - transform.translation) / time.dt(),
+            false => vec2!(0.0, 0.0),
+        };
+        transform.translation = next;
+        if slamming {
+            let aabb = collider.compute_local_aabb();
+            let size = aabb.maxs - aabb.mins;
+            crushed.push((next, size));
+        }
+    }
+    for (position, size) in crushed {
+        // A bit taller, to catch what's right underneath
+        let area = Collider::rect(size.x, size.y + 2.0 * CRUSH_MARGIN);
+        let hits = physics::overlap_shape(
+            world,
+            &*area,
+            position - vec2!(0.0, CRUSH_MARGIN),
+            0.0,
+            QueryFilter::new(Layers::KINEMATIC),
+        );
+        for e in hits {
+            let Ok(mut health) = world.get::<&mut Health>(e) else {
+                continue;
+            };
+            if health.now > 0.0 {
+                let amount = health.now;
+                health.damage(amount, DamageSource::Environment(Environment::Crusher));
+                log::info!("{e:?} was crushed.");
+            }
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use hecs::{ World, Entity };
+
+use crate::{
+    math::{ Vec2, vec2 },
+    physics::{ Collider, FixedBody, OneWay, Material },
+    render::{ Sprite, Costume },
+    transform::{ Transform, NetworkPosition },
+    platform::Time,
+    timescale::TimeScale, spawn::SpawnPoint,
+    pickup,
+};
+
+pub mod format;
+pub mod generate;
+pub mod reach;
+mod hazard;
+mod gadget;
+mod destructible;
+mod bounds;
+mod rotation;
+
+pub use format::Level;
+pub use rotation::{ Rotation, rotate_levels };
+pub use hazard::{ kill_zones, damage_zones, crushers };
+pub use gadget::{ jump_pads, teleporters };
+pub use destructible::destructibles;
+pub use bounds::{ enforce_bounds, draw_bounds };
+use format::{ Hazard, point };
+
+/// Component for platforms that move on their own.
+#[derive(Debug, Clone)]
+pub enum Motion {
+    /// Travel through `points` in a loop at a constant `speed`.
+    Path {
+        points: Vec<Vec2<f32>>,
+        speed: f32,
+        /// Index of the point currently travelled towards.
+        target: usize,
+    },
+    /// Oscillate around `origin`, ie. `origin + amplitude * sin(2πft)`
+    Sine {
+        origin: Vec2<f32>,
+        amplitude: Vec2<f32>,
+        frequency: f32,
+        /// Seconds elapsed since the start of the oscillation.
+        time: f32,
+    },
+}
+
+fn platform(world: &mut World, e: Entity, pos: Vec2<f32>, width: f32) {
+    world.spawn_at(e, (
+        Collider::rect(width, 20.0),
+        FixedBody::default(),
+        Sprite::new(Costume::Platform {
+            position: pos,
+            width
+        }),
+        Transform {
+            translation: pos,
+            rotation: 0.0,
+        },
+    ));
+}
+
+/// Solid level geometry of any shape.
+fn solid(world: &mut World, e: Entity, pos: Vec2<f32>, collider: Collider) {
+    let transform = Transform {
+        translation: pos,
+        rotation: 0.0,
+    };
+    world.spawn_at(e, (
+        Sprite::outline(&collider, &transform),
+        collider,
+        FixedBody::default(),
+        transform,
+    ));
+}
+
+/// A platform that moves and carries whatever stands on it.
+fn moving_platform(world: &mut World, e: Entity, pos: Vec2<f32>, width: f32, motion: Motion) {
+    platform(world, e, pos, width);
+    world.insert(e, (motion, NetworkPosition, TimeScale::default())).unwrap();
+}
+
+/// Every level embedded in the game, see [format] for their contents.
+pub const LEVELS: &[&str] = &[
+    include_str!("../assets/levels/arena.toml"),
+];
+
+/// Spawn every entity of an already validated level at `handles`,
+/// replacing whatever was there. Handles left over are emptied.
+///
+/// Level entities are replicated by handle, so both ends must pass the
+/// same ones, see [Rotation].
+pub fn instantiate(world: &mut World, level: &Level, handles: &[Entity]) {
+    let mut handles = handles.iter().copied();
+    for spawn in &level.spawns {
+        world.spawn_at(handles.next().unwrap(), (
+            SpawnPoint,
+            Transform {
+                translation: point(spawn.position),
+                rotation: 0.0,
+            },
+        ));
+    }
+    for p in &level.platforms {
+        let pos = point(p.position);
+        let e = handles.next().unwrap();
+        match &p.motion {
+            Some(format::Motion::Path { points, speed }) => {
+                moving_platform(world, e, pos, p.width, Motion::Path {
+                    points: points.iter().copied().map(point).collect(),
+                    speed: *speed,
+                    target: 0,
+                })
+            },
+            Some(format::Motion::Sine { amplitude, frequency }) => {
+                moving_platform(world, e, pos, p.width, Motion::Sine {
+                    origin: pos,
+                    amplitude: point(*amplitude),
+                    frequency: *frequency,
+                    time: 0.0,
+                })
+            },
+            None => platform(world, e, pos, p.width),
+        }
+        if p.one_way {
+            world.insert_one(e, OneWay).unwrap();
+        }
+        world.insert_one(e, Material::from(p.material)).unwrap();
+        if let Some(hitpoints) = p.health {
+            destructible::destructible(world, e, hitpoints, p.respawn, p.width);
+        }
+    }
+    for s in &level.solids {
+        // Validated to be convex, or made of convex parts
+        let Some(collider) = s.collider() else {
+            continue;
+        };
+        let e = handles.next().unwrap();
+        solid(world, e, point(s.position), collider);
+        world.insert_one(e, Material::from(s.material)).unwrap();
+    }
+    for hazard in &level.hazards {
+        match hazard {
+            Hazard::Kill { min, max } => {
+                hazard::kill_zone(world, handles.next().unwrap(), point(*min), point(*max));
+            },
+            Hazard::Damage { min, max, dps } => {
+                hazard::damage_zone(world, handles.next().unwrap(), point(*min), point(*max), *dps);
+            },
+            Hazard::Crusher { position, size, travel, period } => {
+                hazard::crusher(world, handles.next().unwrap(), point(*position), point(*size), *travel, *period);
+            },
+        }
+    }
+    for pad in &level.jump_pads {
+        let e = handles.next().unwrap();
+        gadget::jump_pad(world, e, point(pad.position), pad.width, point(pad.velocity));
+    }
+    for pair in &level.teleporters {
+        let a = (handles.next().unwrap(), point(pair.a));
+        let b = (handles.next().unwrap(), point(pair.b));
+        gadget::teleporter_pair(world, a, b);
+    }
+    for p in &level.pickups {
+        pickup::pickup(world, handles.next().unwrap(), point(p.position), p.kind, p.respawn);
+    }
+    world.spawn_at(handles.next().unwrap(), (level.bounds,));
+    for e in handles {
+        world.spawn_at(e, ());
+    }
+}
+
+/// System that moves platforms along their [Motion]
+pub fn move_platforms(world: &mut World, time: &Time) {
+    if cfg!(client) {
+        return;
+    }
+    type Query<'a> = (
+        &'a mut Motion,
+        &'a mut FixedBody,
+        &'a mut Transform,
+        Option<&'a TimeScale>,
+    );
+    for (_, (motion, fb, transform, scale)) in world.query_mut::<Query>() {
+        let scale = scale
+            .map(|s| s.0)
+            .unwrap_or(1.0);
+        let dt = time.dt() * scale;
+        let next = match motion {
+            Motion::Path { points, speed, target } => {
+                let Some(&goal) = points.get(*target) else {
+                    continue;
+                };
+                let delta = goal - transform.translation;
+                let step = *speed * dt;
+                // Arrived, head to the next point
+                if delta.norm() <= step {
+                    *target = (*target + 1) % points.len();
+                    goal
+                } else {
+                    transform.translation + delta.normalize() * step
+                }
+            },
+            Motion::Sine { origin, amplitude, frequency, time } => {
+                *time += dt;
+                let t = std::f32::consts::TAU * *frequency * *time;
+                *origin + *amplitude * t.sin()
+            },
+        };
+        // Velocity in real time, so riders are carried exactly
+        fb.velocity = match time.dt() > 0.0 {
+            true => (next - transform.translation) / time.dt(),
+            false => vec2!(0.0, 0.0),
+        };
+        transform.translation = next;
+    }
+}
@@ -0,0 +1,359 @@
+//! Offline traversability analysis of levels.
+//!
+//! Every platform and solid is a node of a graph, with an edge wherever
+//! the player's jump arc, see [jump_reach], takes it from one to the
+//! other. Moving platforms connect wherever they are along their motion.
+//! Jump pads and teleporters connect what they stand over to wherever
+//! they lead, assuming players can get into them.
+
+use std::{ collections::VecDeque, fmt };
+
+use crate::{
+    math::{ Vec2, vec2 },
+    player::{ jump_reach, launch_arc },
+};
+
+use super::format::{ Level, Motion, point };
+
+/// Width of the player's collider.
+const PLAYER_WIDTH: f32 = 30.0;
+/// Half the thickness of platforms.
+const PLATFORM_HALF_HEIGHT: f32 = 10.0;
+
+/// The top of a platform at one point of its motion.
+#[derive(Debug, Clone, Copy)]
+struct Span {
+    left: f32,
+    right: f32,
+    top: f32,
+}
+
+/// Something players can stand on.
+#[derive(Debug, Clone)]
+struct Node {
+    /// Where it's defined, ie. `platforms[2]`.
+    at: String,
+    /// Everywhere it can be.
+    spans: Vec<Span>,
+    one_way: bool,
+}
+
+/// Problems found in a level, see [analyze].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Report {
+    /// Platforms no spawn point leads to.
+    pub unreachable: Vec<String>,
+    /// Spawn points over the void, or on a platform that can't be left.
+    pub stuck_spawns: Vec<String>,
+    /// Platforms that can be fallen onto but never left, except by dying.
+    pub pits: Vec<String>,
+}
+
+impl Report {
+    /// Is the level fully traversable?
+    pub fn is_ok(&self) -> bool {
+        self.unreachable.is_empty() && self.stuck_spawns.is_empty() && self.pits.is_empty()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for at in &self.unreachable {
+            writeln!(f, "`{at}` is unreachable")?;
+        }
+        for at in &self.stuck_spawns {
+            writeln!(f, "`{at}` has no way out")?;
+        }
+        for at in &self.pits {
+            writeln!(f, "`{at}` is a pit that can't be climbed out of")?;
+        }
+        Ok(())
+    }
+}
+
+/// Can a player standing on `from` get onto `to`?
+fn can_reach(from: &Span, to: &Span, one_way: bool) -> bool {
+    let dy = to.top - from.top;
+    let gap = (to.left - from.right).max(from.left - to.right);
+    let Some(reach) = jump_reach(dy) else {
+        return false;
+    };
+    if gap > 0.0 || dy <= 0.0 || one_way {
+        return reach >= gap;
+    }
+    // Overlapping and solid overhead, go around it
+    let around = from.left <= to.left - PLAYER_WIDTH || from.right >= to.right + PLAYER_WIDTH;
+    around && reach >= PLAYER_WIDTH
+}
+
+/// Can a player launched from `from` at `velocity`, ie. by a jump pad, get
+/// onto `to`?
+fn can_launch(from: Vec2<f32>, velocity: Vec2<f32>, to: &Span) -> bool {
+    let dy = to.top - from.y;
+    // Steering towards it, mirrored when it's on the left
+    let (side, gap) = match to.left > from.x {
+        true => (1.0, to.left - from.x),
+        false => (-1.0, from.x - to.right),
+    };
+    launch_arc(vec2!(velocity.x * side, velocity.y))
+        .filter(|p| p.y >= dy)
+        .map(|p| p.x)
+        .reduce(f32::max)
+        .is_some_and(|reach| reach >= gap)
+}
+
+/// Build the graph's nodes from a level's geometry.
+fn nodes(level: &Level) -> Vec<Node> {
+    let mut nodes = Vec::new();
+    for (i, p) in level.platforms.iter().enumerate() {
+        let origin = point(p.position);
+        let positions = match &p.motion {
+            Some(Motion::Path { points, .. }) => points
+                .iter()
+                .copied()
+                .map(point)
+                .chain([origin])
+                .collect(),
+            Some(Motion::Sine { amplitude, .. }) => {
+                let amplitude = point(*amplitude);
+                vec![origin - amplitude, origin, origin + amplitude]
+            },
+            None => vec![origin],
+        };
+        nodes.push(Node {
+            at: format!("platforms[{i}]"),
+            spans: positions
+                .iter()
+                .map(|pos| Span {
+                    left: pos.x - p.width / 2.0,
+                    right: pos.x + p.width / 2.0,
+                    top: pos.y + PLATFORM_HALF_HEIGHT,
+                })
+                .collect(),
+            one_way: p.one_way,
+        });
+    }
+    for (i, s) in level.solids.iter().enumerate() {
+        let Some(collider) = s.collider() else {
+            continue;
+        };
+        let aabb = collider.compute_local_aabb();
+        let pos: Vec2<f32> = point(s.position);
+        nodes.push(Node {
+            at: format!("solids[{i}]"),
+            spans: vec![Span {
+                left: pos.x + aabb.mins.x,
+                right: pos.x + aabb.maxs.x,
+                top: pos.y + aabb.maxs.y,
+            }],
+            one_way: false,
+        });
+    }
+    nodes
+}
+
+/// Index of the node a player falls onto from `p`, if any.
+fn landing(nodes: &[Node], p: Vec2<f32>) -> Option<usize> {
+    nodes
+        .iter()
+        .enumerate()
+        .filter_map(|(i, node)| node.spans
+            .iter()
+            .filter(|s| s.left <= p.x && p.x <= s.right && s.top <= p.y)
+            .map(|s| s.top)
+            .reduce(f32::max)
+            .map(|top| (i, top))
+        )
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(i, _)| i)
+}
+
+/// Every node reachable from `sources`, following `edges`.
+fn flood(edges: &[Vec<usize>], sources: impl IntoIterator<Item = usize>) -> Vec<bool> {
+    let mut seen = vec![false; edges.len()];
+    let mut queue = VecDeque::new();
+    for i in sources {
+        seen[i] = true;
+        queue.push_back(i);
+    }
+    while let Some(i) = queue.pop_front() {
+        for &j in &edges[i] {
+            if !seen[j] {
+                seen[j] = true;
+                queue.push_back(j);
+            }
+        }
+    }
+    seen
+}
+
+/// Find everywhere a level can't be traversed with the player's movement.
+pub fn analyze(level: &Level) -> Report {
+    let nodes = nodes(level);
+    let n = nodes.len();
+    // Edges, and the same edges backwards
+    let mut forward = vec![Vec::new(); n];
+    let mut backward = vec![Vec::new(); n];
+    for i in 0..n {
+        for j in (0..n).filter(|&j| j != i) {
+            let reachable = nodes[i].spans.iter().any(|from| nodes[j].spans
+                .iter()
+                .any(|to| can_reach(from, to, nodes[j].one_way))
+            );
+            if reachable {
+                forward[i].push(j);
+                backward[j].push(i);
+            }
+        }
+    }
+    // Jump pads launch players off what they stand over
+    for pad in &level.jump_pads {
+        let position = point(pad.position);
+        let Some(i) = landing(&nodes, position) else {
+            continue;
+        };
+        for j in (0..n).filter(|&j| j != i) {
+            if nodes[j].spans.iter().any(|to| can_launch(position, point(pad.velocity), to)) {
+                forward[i].push(j);
+                backward[j].push(i);
+            }
+        }
+    }
+    // Teleporters lead to whatever their pair stands over, both ways
+    for pair in &level.teleporters {
+        let (Some(a), Some(b)) = (landing(&nodes, point(pair.a)), landing(&nodes, point(pair.b))) else {
+            continue;
+        };
+        for (i, j) in [(a, b), (b, a)] {
+            forward[i].push(j);
+            backward[j].push(i);
+        }
+    }
+
+    let mut report = Report::default();
+    let mut starts = Vec::new();
+    for (i, spawn) in level.spawns.iter().enumerate() {
+        match landing(&nodes, point(spawn.position)) {
+            Some(start) if !forward[start].is_empty() || n == 1 => starts.push(start),
+            Some(start) => {
+                starts.push(start);
+                report.stuck_spawns.push(format!("spawns[{i}]"));
+            },
+            None => report.stuck_spawns.push(format!("spawns[{i}]")),
+        }
+    }
+    let reached = flood(&forward, starts.iter().copied());
+    // Nodes that lead back to a spawn point
+    let recoverable = flood(&backward, starts.iter().copied());
+    for (i, node) in nodes.iter().enumerate() {
+        if !reached[i] {
+            report.unreachable.push(node.at.clone());
+        } else if !recoverable[i] {
+            report.pits.push(node.at.clone());
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::level::{ generate, LEVELS };
+
+    fn level(extra: &str) -> Level {
+        let src = format!(r#"
+            [metadata]
+            name = "Test"
+            [bounds]
+            min = [-2000, -2000]
+            max = [2000, 2000]
+            [[spawns]]
+            position = [0, 100]
+            [[platforms]]
+            position = [0, 0]
+            width = 200
+            [[platforms]]
+            position = [300, 50]
+            width = 200
+            {extra}
+        "#);
+        Level::parse(&src).unwrap()
+    }
+
+    #[test]
+    fn connected_level_is_ok() {
+        let report = analyze(&level(""));
+        assert!(report.is_ok(), "{report}");
+    }
+
+    #[test]
+    fn finds_unreachable_platforms() {
+        let report = analyze(&level("
+            [[platforms]]
+            position = [300, 1500]
+            width = 200
+        "));
+        assert_eq!(report.unreachable, ["platforms[2]"]);
+    }
+
+    #[test]
+    fn finds_stuck_spawns() {
+        let report = analyze(&level("
+            [[spawns]]
+            position = [1000, 100]
+        "));
+        assert_eq!(report.stuck_spawns, ["spawns[1]"]);
+    }
+
+    #[test]
+    fn finds_pits() {
+        let report = analyze(&level("
+            [[platforms]]
+            position = [0, -1500]
+            width = 200
+        "));
+        assert_eq!(report.pits, ["platforms[2]"]);
+        assert!(report.unreachable.is_empty());
+    }
+
+    #[test]
+    fn jump_pads_and_teleporters_connect() {
+        let unreachable = "
+            [[platforms]]
+            position = [600, 600]
+            width = 200
+        ";
+        let pad = format!("{unreachable}
+            [[jump_pads]]
+            position = [300, 70]
+            width = 80
+            velocity = [400, 5000]
+        ");
+        let report = analyze(&level(&pad));
+        assert!(report.unreachable.is_empty(), "{report}");
+        let teleporters = format!("{unreachable}
+            [[teleporters]]
+            a = [0, 50]
+            b = [600, 650]
+        ");
+        let report = analyze(&level(&teleporters));
+        assert!(report.is_ok(), "{report}");
+    }
+
+    #[test]
+    fn embedded_levels_are_traversable() {
+        for src in LEVELS {
+            let level = Level::parse(src).unwrap();
+            let report = analyze(&level);
+            assert!(report.is_ok(), "{}:\n{report}", level.metadata.name);
+        }
+    }
+
+    #[test]
+    fn generated_arenas_are_traversable() {
+        for seed in 0..20 {
+            let report = analyze(&generate::arena(seed));
+            assert!(report.is_ok(), "seed {seed}:\n{report}");
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use hecs::{ Entity, World, With, Without };
+
+use crate::{
+    platform::{ Socket, Time },
+    network::Packet,
+    transform::Transform,
+    player::Player,
+    health::Dead,
+    spawn,
+};
+
+use super::{ LEVELS, Level, instantiate, generate, reach, format::MAX_ENTITIES };
+
+/// Seconds a match lasts before moving on to the next level.
+const MATCH_DURATION: f32 = 300.0;
+/// Seconds between repeats of the current level to clients, since
+/// packets can get lost.
+const RESEND_INTERVAL: f32 = 1.0;
+
+/// Levels played one after the other, one per match. Every valid level
+/// of [LEVELS] is played, followed by a generated arena.
+pub struct Rotation {
+    /// Every valid level of [LEVELS].
+    levels: Vec<Level>,
+    /// Handles reserved for level entities, identical on every end.
+    handles: Vec<Entity>,
+    /// Index in `levels` of the one being played.
+    current: usize,
+    /// Number of matches played, tells rematches of a level apart.
+    round: u32,
+    /// Seconds left in the match.
+    time_left: f32,
+    /// Seconds before the current level is sent again.
+    resend: f32,
+}
+
+impl Rotation {
+    /// Parse every level and instantiate the first one. Levels that
+    /// can't be fully traversed are still played, with a warning.
+    ///
+    /// Must be called at the same point on the server and clients since
+    /// it reserves level entity handles.
+    pub fn new(world: &mut World) -> Self {
+        let levels = LEVELS
+            .iter()
+            .filter_map(|src| Level::parse(src)
+                .map_err(|e| log::error!("{e}"))
+                .ok()
+            )
+            .collect::<Vec<_>>();
+        for level in &levels {
+            let report = reach::analyze(level);
+            if !report.is_ok() {
+                log::warn!("Level \"{}\" isn't fully traversable:\n{report}", level.metadata.name);
+            }
+        }
+        let handles = world
+            .reserve_entities(MAX_ENTITIES as u32)
+            .collect::<Vec<_>>();
+        let mut rotation = Self {
+            levels,
+            handles,
+            current: 0,
+            round: 0,
+            time_left: MATCH_DURATION,
+            resend: 0.0,
+        };
+        rotation.switch(world, 0, 0);
+        rotation
+    }
+
+    /// Number of levels in the rotation.
+    fn len(&self) -> usize {
+        self.levels.len() + 1
+    }
+
+    /// Replace the level being played with the `index`th of the rotation.
+    fn switch(&mut self, world: &mut World, index: usize, round: u32) {
+        let level = match self.levels.get(index) {
+            Some(level) => level.clone(),
+            // Seeded by the round so that clients generate the same one
+            None if index == self.levels.len() => generate::arena(round as u64),
+            None => return,
+        };
+        let metadata = &level.metadata;
+        match metadata.author.as_str() {
+            "" => log::info!("Loading level \"{}\"", metadata.name),
+            author => log::info!("Loading level \"{}\" by {author}", metadata.name),
+        }
+        if !metadata.description.trim().is_empty() {
+            log::info!("{}", metadata.description.trim());
+        }
+        instantiate(world, &level, &self.handles);
+        self.current = index;
+        self.round = round;
+        self.time_left = MATCH_DURATION;
+    }
+}
+
+/// System that moves on to the next level at the end of every match,
+/// and keeps clients on the same level as the server.
+pub fn rotate_levels(world: &mut World, socket: &Socket, time: &Time, rotation: &mut Rotation) {
+    if cfg!(client) {
+        for (_, packet) in socket.packets() {
+            let &Packet::LevelChange { level, round } = packet else {
+                continue;
+            };
+            if (level, round) != (rotation.current, rotation.round) {
+                rotation.switch(world, level, round);
+            }
+        }
+        return;
+    }
+    // Newcomers don't wait for the next resend
+    for &connection in socket.connections() {
+        socket.send(connection, &Packet::LevelChange {
+            level: rotation.current,
+            round: rotation.round,
+        });
+    }
+    rotation.time_left -= time.dt();
+    rotation.resend -= time.dt();
+    if rotation.time_left <= 0.0 {
+        let next = (rotation.current + 1) % rotation.len();
+        rotation.switch(world, next, rotation.round.wrapping_add(1));
+        rotation.resend = 0.0;
+        // Everyone starts over on the new level
+        spawn::forget_all_deaths(world);
+        let players = world
+            .query_mut::<Without<With<(), &Player>, &Dead>>()
+            .into_iter()
+            .map(|(e, _)| e)
+            .collect::<Vec<_>>();
+        for e in players {
+            let pos = spawn::select(world, e);
+            if let Ok(mut transform) = world.get::<&mut Transform>(e) {
+                transform.translation = pos;
+            }
+            socket.broadcast(&Packet::PlayerRespawn(e, pos));
+        }
+    }
+    // TODO: reliable transport
+    if rotation.resend <= 0.0 {
+        rotation.resend = RESEND_INTERVAL;
+        socket.broadcast(&Packet::LevelChange {
+            level: rotation.current,
+            round: rotation.round,
+        });
+    }
+}
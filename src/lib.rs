@@ -1,6 +1,10 @@
 use platform::{ Canvas, Gamepad, Socket, Time };
 use hecs::World;
 
+mod timescale;
+mod debug;
+mod spawn;
+mod pickup;
 mod transform;
 mod platform;
 mod network;
@@ -25,7 +29,8 @@ pub fn main() {
         .collect::<Vec<_>>()
         .into_iter();
 
-    level::instantiate(&mut world);
+    let mut rotation = level::Rotation::new(&mut world);
+    ability::validate();
 
     platform::run(move || {
         socket.poll();
@@ -33,8 +38,12 @@ pub fn main() {
 
         player::networked_instantiate(&mut world, &socket, &mut reserved);
         player::networked_despawn(&mut world, &socket);
+        level::rotate_levels(&mut world, &socket, &time, &mut rotation);
+        timescale::update(&mut world, &socket, &time);
+        spawn::forget_deaths(&mut world, &time);
         health::respawn_players(&mut world, &socket, &time);
         input::update(&mut world, &input);
+        debug::toggle(&input, &socket);
         input::network_player_commands(&mut world, &socket);
         // TODO: client-side prediction
         if cfg!(server) {
@@ -42,31 +51,44 @@ pub fn main() {
         }
         transform::local_to_world(&mut world);
         ability::position_shield(&mut world);
-        ability::bubble_shield_controller(&mut world, &socket, &time);
-        ability::push_controller(&mut world, &time, &socket);
-        ability::freeze_controller(&mut world, &mut time, &socket);
-        ability::lightning_controller(&mut world, &mut time, &socket);
+        ability::update_abilities(&mut world, &socket, &time, ability::Stage::Movement);
+        level::move_platforms(&mut world, &time);
+        level::crushers(&mut world, &time);
         physics::compute_gravity(&mut world, &time);
         physics::compute_kinematics(&mut world, &time);
         physics::resolve_collisions(&mut world, &time);
         physics::compute_collisions(&mut world);
+        physics::compute_triggers(&mut world);
+        level::enforce_bounds(&mut world);
         transform::networked_position(&mut world, &socket);
-        level::void_damage(&mut world);
+        level::kill_zones(&mut world);
+        level::damage_zones(&mut world, &socket, &time);
+        level::jump_pads(&mut world, &socket);
+        level::teleporters(&mut world, &socket, &time);
+        pickup::pickups(&mut world, &socket, &time);
         ability::toggle_abilities(&mut world, &socket);
         ability::gun_controller(&mut world, &socket, &time);
+        ability::update_abilities(&mut world, &socket, &time, ability::Stage::Guns);
         input::network_look_direction(&mut world, &socket);
         input::follow_look_direction(&mut world);
-        ability::heal_controller(&mut world, &time, &socket);
+        ability::update_abilities(&mut world, &socket, &time, ability::Stage::Aim);
         bullet::impact_and_damage(&mut world, &socket);
+        level::destructibles(&mut world, &socket, &time);
         bullet::network_instantiate(&mut world, &socket);
         bullet::despawn_time_to_live(&mut world, &time);
         render::animate_player_sprites(&mut world);
         render::animate_bullet_sprites(&mut world);
         render::animate_handheld_sprites(&mut world);
-        render::animate_bubble_shield_sprite(&mut world);
+        ability::animate_abilities(&mut world);
         render::animate_health_bar_sprites(&mut world);
         render::animate_shadow_sprites(&mut world);
+        render::animate_platform_sprites(&mut world);
+        render::animate_shape_sprites(&mut world);
+        level::draw_bounds(&mut world, &canvas);
         render::draw_sprites(&mut world, &canvas);
         render::draw_cooldowns(&socket, &canvas);
+        render::draw_kill_feed(&mut world, &socket, &canvas);
+        debug::draw_colliders(&mut world);
+        debug::flush(&canvas, &socket);
     });
 }
\ No newline at end of file
@@ -3,7 +3,8 @@ use hecs::Entity;
 use crate::{
     input::{Input, LookDirection},
     math::Vec2,
-    platform::Connection, ability::AbilityKind, render::Costume
+    platform::Connection, ability::AbilityKind, render::Costume, debug::Line,
+    timescale::TimeModifier, health::DamageSource, pickup::PickupKind,
 };
 
 /// Shorthand for iterator of reserved entity IDs
@@ -15,7 +16,7 @@ pub trait NetEntities: Iterator<Item = Entity> { }
 #[derive(Debug, Clone)]
 pub enum Packet {
     /// Server -> Clients
-    PlayerSpawn(Entity, Connection, [AbilityKind; 4], usize),
+    PlayerSpawn(Entity, Connection, [AbilityKind; 4], usize, Vec2<f32>),
     /// Server -> Clients
     PlayerDespawn(Entity),
     /// Client -> Server
@@ -38,9 +39,46 @@ pub enum Packet {
     EffectSpawn(Costume),
     /// Server -> Clients
     PlayerRespawn(Entity, Vec2<f32>),
+    /// Server -> Clients
+    PlayerKilled {
+        victim: Entity,
+        source: DamageSource,
+    },
     /// Server -> Client
     CooldownStart {
         binding: usize,
         duration: f32,
     },
-}
\ No newline at end of file
+    /// Server -> Clients
+    TimeModifier(TimeModifier),
+    /// Server -> Clients
+    TerrainBroken(Entity),
+    /// Server -> Clients
+    TerrainRestored(Entity),
+    /// Server -> Clients
+    PickupTaken(Entity),
+    /// Server -> Clients
+    PickupRestored(Entity),
+    /// Server -> Clients
+    PlayerBoost {
+        player: Entity,
+        kind: PickupKind,
+        duration: f32,
+    },
+    /// Server -> Clients
+    Grapple {
+        owner: Entity,
+        /// What the hook is stuck in and where relative to it, if it is.
+        hook: Option<(Entity, Vec2<f32>)>,
+    },
+    /// Server -> Clients
+    LevelChange {
+        /// Index in the level rotation.
+        level: usize,
+        round: u32,
+    },
+    /// Client -> Server
+    DebugOverlay(bool),
+    /// Server -> Client
+    DebugLine(Line),
+}
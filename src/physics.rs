@@ -1,23 +1,28 @@
-use std::ops::Deref;
+use std::ops::{ Deref, BitOr, Not };
 
-use hecs::{ Entity, World, With };
+use hecs::{ Entity, EntityRef, World, Without };
+use nalgebra::Isometry2;
 use parry2d::{
-    shape::{ Cuboid, Ball, Shape },
+    shape::{ Cuboid, Ball, Capsule, ConvexPolygon, Compound, SharedShape, Shape },
     query::{ self, Contact, Ray },
 };
 use smallvec::SmallVec;
+use serde::Deserialize;
 
 use crate::{
     math::{ Vec2, vec2 },
     transform::Transform,
-    platform::Time, ability::TimeScale,
+    platform::Time, timescale::TimeScale, debug,
 };
 
 /// Collider component
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub enum Collider {
     Box(Cuboid),
     Circle(Ball),
+    Capsule(Capsule),
+    Polygon(ConvexPolygon),
+    Compound(Compound),
 }
 
 impl Collider {
@@ -30,6 +35,103 @@ impl Collider {
     pub fn circle(r: f32) -> Self {
         Self::Circle(Ball::new(r))
     }
+
+    /// Creates a new vertical [Collider::Capsule] spanning a `w` by `h`
+    /// rectangle, ie. a box with rounded ends.
+    pub fn capsule(w: f32, h: f32) -> Self {
+        let r = w / 2.0;
+        Self::Capsule(Capsule::new_y((h / 2.0 - r).max(0.0), r))
+    }
+
+    /// Creates a new [Collider::Polygon] from `points` in either winding
+    /// order, if they form a convex polygon.
+    pub fn polygon(points: &[Vec2<f32>]) -> Option<Self> {
+        if points.len() < 3 {
+            return None;
+        }
+        // Angle turned at every vertex, going around the polygon
+        let turns = (0..points.len())
+            .map(|i| {
+                let [a, b, c] = [0, 1, 2].map(|j| points[(i + j) % points.len()]);
+                let (ab, bc) = (b - a, c - b);
+                ab.perp(&bc).atan2(ab.dot(&bc))
+            })
+            .collect::<Vec<_>>();
+        // Convex polygons turn the same way at every vertex, once around
+        let ccw = turns.iter().all(|&t| t >= 0.0);
+        let cw = turns.iter().all(|&t| t <= 0.0);
+        let total = turns.iter().sum::<f32>().abs();
+        if !(ccw || cw) || (total - std::f32::consts::TAU).abs() > 0.01 {
+            return None;
+        }
+        let mut points = points
+            .iter()
+            .map(|&p| p.into())
+            .collect::<Vec<_>>();
+        if cw {
+            points.reverse();
+        }
+        ConvexPolygon::from_convex_polyline(points).map(Self::Polygon)
+    }
+
+    /// Creates a new [Collider::Compound] from `parts` offset from the
+    /// origin. Parts can't be compound themselves.
+    pub fn compound(parts: Vec<(Vec2<f32>, Collider)>) -> Self {
+        let shapes = parts
+            .into_iter()
+            .filter_map(|(offset, part)| {
+                let shape = match part {
+                    Collider::Box(s) => SharedShape::new(s),
+                    Collider::Circle(s) => SharedShape::new(s),
+                    Collider::Capsule(s) => SharedShape::new(s),
+                    Collider::Polygon(s) => SharedShape::new(s),
+                    Collider::Compound(_) => return None,
+                };
+                Some((Isometry2::translation(offset.x, offset.y), shape))
+            })
+            .collect();
+        Self::Compound(Compound::new(shapes))
+    }
+
+    /// Outline of this collider as a list of polygons, relative to its
+    /// origin. Curved bits are approximated.
+    pub fn outline(&self) -> Vec<Vec<Vec2<f32>>> {
+        outline(self.deref())
+    }
+}
+
+/// See [Collider::outline]
+fn outline(shape: &dyn Shape) -> Vec<Vec<Vec2<f32>>> {
+    /// Number of segments per curve
+    const SUBDIVISIONS: u32 = 16;
+
+    let points = if let Some(s) = shape.as_cuboid() {
+        s.to_polyline()
+    } else if let Some(s) = shape.as_ball() {
+        s.to_polyline(SUBDIVISIONS)
+    } else if let Some(s) = shape.as_capsule() {
+        s.to_polyline(SUBDIVISIONS / 2)
+    } else if let Some(s) = shape.as_convex_polygon() {
+        s.points().to_vec()
+    } else if let Some(s) = shape.as_compound() {
+        return s
+            .shapes()
+            .iter()
+            .flat_map(|(iso, part)| {
+                outline(part.deref())
+                    .into_iter()
+                    .map(|poly| poly
+                        .into_iter()
+                        .map(|p| iso * p)
+                        .collect()
+                    )
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+    } else {
+        return Vec::new();
+    };
+    vec![points.into_iter().map(|p| p.coords).collect()]
 }
 
 impl Deref for Collider {
@@ -39,6 +141,25 @@ impl Deref for Collider {
         match self {
             Collider::Box(s) => s,
             Collider::Circle(s) => s,
+            Collider::Capsule(s) => s,
+            Collider::Polygon(s) => s,
+            Collider::Compound(s) => s,
+        }
+    }
+}
+
+impl std::fmt::Debug for Collider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Collider::Box(s) => f.debug_tuple("Box").field(s).finish(),
+            Collider::Circle(s) => f.debug_tuple("Circle").field(s).finish(),
+            Collider::Capsule(s) => f.debug_tuple("Capsule").field(s).finish(),
+            Collider::Polygon(s) => f.debug_tuple("Polygon").field(s).finish(),
+            // `Compound` isn't `Debug`
+            Collider::Compound(s) => f
+                .debug_tuple("Compound")
+                .field(&s.shapes().len())
+                .finish(),
         }
     }
 }
@@ -48,6 +169,40 @@ impl Deref for Collider {
 #[derive(Debug, Default, Clone)]
 pub struct Collisions(pub SmallVec<[Entity; 8]>);
 
+/// Component for colliders that detect overlaps without resolving them,
+/// like kill zones or pickups. Overlaps are reported as [TriggerEvent]s.
+#[derive(Debug, Default, Clone)]
+pub struct Trigger {
+    /// Entities overlapping as of this frame.
+    overlaps: SmallVec<[Entity; 8]>,
+    /// What happened to the overlaps this frame.
+    events: SmallVec<[(Entity, TriggerEvent); 8]>,
+}
+
+/// An entity's overlap with a [Trigger].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerEvent {
+    /// Started overlapping this frame.
+    Enter,
+    /// Has been overlapping since a previous frame.
+    Stay,
+    /// Stopped overlapping this frame, or was despawned.
+    Exit,
+}
+
+impl Trigger {
+    /// Iterate over this frame's events and the entity they concern.
+    pub fn events(&self) -> impl Iterator<Item = &(Entity, TriggerEvent)> {
+        self.events.iter()
+    }
+
+    /// Iterate over the entities currently overlapping, ie. those that
+    /// [TriggerEvent::Enter]'ed or [TriggerEvent::Stay]'ed.
+    pub fn overlaps(&self) -> impl Iterator<Item = &Entity> {
+        self.overlaps.iter()
+    }
+}
+
 /// Component for entities whose position is affected by its velocity
 /// and collisions.
 /// 
@@ -62,10 +217,28 @@ pub struct KinematicBody {
 /// Component for entities whose position is unaffected by collisions,
 /// like a wall or the ground.
 #[derive(Debug, Default, Clone)]
-pub struct FixedBody;
+pub struct FixedBody {
+    /// Linear velocity, for moving platforms. [Grounded] bodies standing
+    /// on this one are carried along with it.
+    pub velocity: Vec2<f32>,
+}
 
 // TODO: DynamicBody for the fun destructible stuff
 
+/// Component for [FixedBody]'s that are only solid from above, relative
+/// to the colliding body's [Gravity]. Bodies can jump up through them
+/// and drop back down with [DropThrough].
+#[derive(Debug, Default, Clone)]
+pub struct OneWay;
+
+/// Component for kinematic bodies that can fall through [OneWay]
+/// platforms on demand.
+#[derive(Debug, Default, Clone)]
+pub struct DropThrough {
+    /// Seconds left ignoring [OneWay] platforms.
+    pub time: f32,
+}
+
 /// Component denoting an entity as being affected by gravity.
 #[derive(Debug, Clone)]
 pub struct Gravity {
@@ -81,6 +254,84 @@ impl Default for Gravity {
     }
 }
 
+/// Component for a collider's surface properties. Colliders without
+/// one behave like [Material::default].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Material {
+    /// How quickly bodies touching this slow down, relative to regular
+    /// ground(`1.0`).
+    pub friction: f32,
+    /// Fraction of the velocity into this surface that bounces back,
+    /// `0.0..=1.0`. The bounciest of two colliders wins.
+    pub restitution: f32,
+    /// Gameplay tag of the surface.
+    pub kind: SurfaceKind,
+}
+
+/// Kinds of surfaces, see [Material].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SurfaceKind {
+    #[default]
+    Normal,
+    Ice,
+    Mud,
+    Bouncy,
+}
+
+impl Material {
+    /// Slippery, hard to start or stop moving on.
+    pub const ICE: Self = Self {
+        friction: 0.15,
+        restitution: 0.0,
+        kind: SurfaceKind::Ice,
+    };
+    /// Sticky, slow to move through.
+    pub const MUD: Self = Self {
+        friction: 3.0,
+        restitution: 0.0,
+        kind: SurfaceKind::Mud,
+    };
+    /// Trampoline-like.
+    pub const BOUNCY: Self = Self {
+        friction: 1.0,
+        restitution: 0.9,
+        kind: SurfaceKind::Bouncy,
+    };
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self {
+            friction: 1.0,
+            restitution: 0.0,
+            kind: SurfaceKind::Normal,
+        }
+    }
+}
+
+impl From<SurfaceKind> for Material {
+    fn from(kind: SurfaceKind) -> Self {
+        match kind {
+            SurfaceKind::Normal => Self::default(),
+            SurfaceKind::Ice => Self::ICE,
+            SurfaceKind::Mud => Self::MUD,
+            SurfaceKind::Bouncy => Self::BOUNCY,
+        }
+    }
+}
+
+/// A contact between a kinematic body and a [FixedBody].
+#[derive(Debug, Clone, Copy)]
+pub struct Surface {
+    /// Normal of the surface at the contact, pointing towards the body.
+    pub normal: Vec2<f32>,
+    /// Velocity of the surface, ie. of a moving platform.
+    pub velocity: Vec2<f32>,
+    /// Material of the surface.
+    pub material: Material,
+}
+
 /// Is this entity touching a "ground" static body?
 /// Definition of ground here relates to this entity's [Gravity], ie.
 /// this component is essentially useless without it.
@@ -88,7 +339,7 @@ impl Default for Gravity {
 pub enum Grounded {
     /// The entity is touching the ground and has been for `time` seconds
     /// seconds.
-    Yes { time: f32 },
+    Yes { time: f32, surface: Surface },
     /// The entity is *not* touching the ground and has *not* been
     /// for `time` seconds. Useful for jump grace period.
     No { time: f32 },
@@ -100,6 +351,14 @@ impl Default for Grounded {
     }
 }
 
+/// Walls an entity is touching on either side, where left and right
+/// are relative to its [Gravity]. Updated alongside [Grounded].
+#[derive(Debug, Default, Clone)]
+pub struct Walls {
+    pub left: Option<Surface>,
+    pub right: Option<Surface>,
+}
+
 /// System that adds gravity to every relevant entity.
 pub fn compute_gravity(world: &mut World, time: &Time) {
     /// Query kinematic bodies
@@ -132,7 +391,7 @@ pub fn compute_kinematics(world: &mut World, time: &Time) {
 }
 
 /// System that resolves intersections between kinematic/fixed bodies.
-/// Also updates the [Grounded] component.
+/// Also updates the [Grounded] and [Walls] components.
 pub fn resolve_collisions(world: &mut World, time: &Time) {
     /// Minimum query to have a collision.
     type KinematicQuery<'a> = (
@@ -140,24 +399,48 @@ pub fn resolve_collisions(world: &mut World, time: &Time) {
         &'a mut KinematicBody,
         &'a Collider,
         Option<(&'a mut Grounded, &'a Gravity)>,
+        Option<&'a mut Walls>,
+        Option<&'a DropThrough>,
+        Option<&'a Material>,
         Option<&'a TimeScale>,
     );
-    type FixedQuery<'a> = With<(&'a Transform, &'a Collider), &'a FixedBody>;
+    type FixedQuery<'a> = (
+        &'a Transform,
+        &'a Collider,
+        &'a FixedBody,
+        Option<&'a OneWay>,
+        Option<&'a Material>,
+    );
+    /// Penetration depth tolerated on top of a frame's motion before a
+    /// [OneWay] platform considers the body to have come from below.
+    const ONE_WAY_SLOP: f32 = 2.0;
+    /// Minimum alignment of a contact normal with gravity to be ground,
+    /// ie. slopes up to 60°.
+    const GROUND_THRESHOLD: f32 = 0.5;
+    /// Minimum alignment of a contact normal with the horizontal to be
+    /// a wall.
+    const WALL_THRESHOLD: f32 = 0.7;
 
     // Simple O(n^2) `a` intersects `b` test.
-    for (_, (t1, kb, c1, mut ground, scale)) in &mut world.query::<KinematicQuery>() {
+    for (_, (t1, kb, c1, mut ground, mut walls, drop, m1, scale)) in &mut world.query::<KinematicQuery>() {
         // Cache the body's gravity for groundedness computations.
         let gravity = match ground {
             Some((_, g)) => g.acceleration.normalize(),
             _ => vec2!(0.0, 0.0)
         };
+        // Right-hand side relative to gravity
+        let right = vec2!(-gravity.y, gravity.x);
         let scale = scale
             .map(|s| s.0)
             .unwrap_or(1.0);
-        // Find at least one "ground"
-        let mut grounded = false;
+        // Find the "ground" most aligned with gravity
+        let mut floor: Option<(Surface, f32)> = None;
+        let mut left_wall = None;
+        let mut right_wall = None;
+
+        let m1 = m1.copied().unwrap_or_default();
 
-        for (_, (t2, c2)) in &mut world.query::<FixedQuery>() {
+        for (_, (t2, c2, fb, one_way, m2)) in &mut world.query::<FixedQuery>() {
             // Compute collision:
             let Ok(contact) = query::contact(
                 &(&*t1).into(),
@@ -169,34 +452,91 @@ pub fn resolve_collisions(world: &mut World, time: &Time) {
                 continue;
             };
             // Compute the contact normal and correct overlaps.
-            let Some(Contact { dist, normal1, .. }) = contact else {
+            let Some(Contact { dist, normal1, point1, .. }) = contact else {
                 continue;
             };
 
             if dist <= 0.0 {
                 let n = normal1.into_inner();
-                
+
+                // One-way platforms only catch bodies falling onto them,
+                // ie. the overlap is explained by this frame's motion.
+                if one_way.is_some() {
+                    let falling = kb.velocity.dot(&gravity) * time.dt() * scale;
+                    let dropping = drop
+                        .map(|d| d.time > 0.0)
+                        .unwrap_or(false);
+                    if dropping
+                    || n.dot(&gravity) <= GROUND_THRESHOLD
+                    || -dist > falling.max(0.0) + ONE_WAY_SLOP {
+                        continue;
+                    }
+                }
                 // Remove component of translation along contact normal.
                 t1.translation += n * dist;
-                // Remove component of velocity along contact normal.
-                kb.velocity -= n * n.dot(&kb.velocity);
+                // Remove component of velocity along contact normal, and
+                // bounce back if either surface is bouncy.
+                let m2 = m2.copied().unwrap_or_default();
+                let restitution = m1.restitution.max(m2.restitution);
+                let vn = n.dot(&kb.velocity);
+                if vn > 0.0 {
+                    kb.velocity -= n * vn * (1.0 + restitution);
+                } else {
+                    kb.velocity -= n * vn;
+                }
+
+                let surface = Surface {
+                    normal: -n,
+                    velocity: fb.velocity,
+                    material: m2,
+                };
+                debug::contact(point1.coords, surface.normal);
                 // Compute groundedness
-                grounded |= n.dot(&gravity.normalize()) > 0.5;
+                let alignment = n.dot(&gravity);
+                if alignment > GROUND_THRESHOLD {
+                    if floor.map_or(true, |(_, a)| alignment > a) {
+                        floor = Some((surface, alignment));
+                    }
+                // Compute walls
+                } else if n.dot(&right) > WALL_THRESHOLD {
+                    right_wall = Some(surface);
+                } else if n.dot(&right) < -WALL_THRESHOLD {
+                    left_wall = Some(surface);
+                }
             }
         }
+        // Ride along moving platforms
+        if let Some((surface, _)) = floor {
+            t1.translation += surface.velocity * time.dt();
+        }
         // (Optionally) compute groundedness
         if let Some((g, _)) = &mut ground {
-            **g = match g.clone() {
-                Grounded::Yes { time: t } if grounded => Grounded::Yes {
-                    time: t + time.dt() * scale,
+            let dt = time.dt() * scale;
+            **g = match (g.clone(), floor) {
+                (Grounded::Yes { time: t, .. }, Some((surface, ..))) => Grounded::Yes {
+                    time: t + dt,
+                    surface,
+                },
+                (Grounded::No { time: t }, None) => Grounded::No {
+                    time: t + dt,
+                },
+                (Grounded::No { .. }, Some((surface, ..))) => Grounded::Yes {
+                    time: 0.0,
+                    surface,
                 },
-                Grounded::No { time: t } if !grounded => Grounded::No {
-                    time: t + time.dt() * scale,
+                // Keep the momentum of the platform left, ie. when
+                // jumping off a moving one
+                (Grounded::Yes { surface, .. }, None) => {
+                    kb.velocity += surface.velocity;
+                    Grounded::No { time: 0.0 }
                 },
-                Grounded::No { .. } => Grounded::Yes { time: 0.0 },
-                Grounded::Yes { .. } => Grounded::No { time: 0.0 },
             };
         }
+        // (Optionally) compute walls
+        if let Some(walls) = &mut walls {
+            walls.left = left_wall;
+            walls.right = right_wall;
+        }
     }
 }
 
@@ -204,7 +544,8 @@ pub fn resolve_collisions(world: &mut World, time: &Time) {
 pub fn compute_collisions(world: &mut World) {
     for (e1, (t1, c1, collisions)) in &mut world.query::<(&Transform, &Collider, &mut Collisions)>() {
         collisions.0.clear();
-        for (e2, (t2, c2)) in &mut world.query::<(&Transform, &Collider)>() {
+        // Triggers are reported separately, see [compute_triggers]
+        for (e2, (t2, c2)) in &mut world.query::<Without<(&Transform, &Collider), &Trigger>>() {
             // Don't collide with self!
             if e1 == e2 {
                 continue;
@@ -225,6 +566,142 @@ pub fn compute_collisions(world: &mut World) {
     }
 }
 
+/// System that computes [Trigger] overlaps and their events.
+pub fn compute_triggers(world: &mut World) {
+    for (e1, (t1, c1, trigger)) in &mut world.query::<(&Transform, &Collider, &mut Trigger)>() {
+        let mut overlaps = SmallVec::<[Entity; 8]>::new();
+        // Triggers don't trigger each other
+        for (e2, (t2, c2)) in &mut world.query::<Without<(&Transform, &Collider), &Trigger>>() {
+            if e1 == e2 {
+                continue;
+            }
+            let Ok(true) = query::intersection_test(
+                &(&*t1).into(),
+                c1.deref(),
+                &t2.into(),
+                c2.deref(),
+            ) else {
+                continue;
+            };
+            overlaps.push(e2);
+        }
+        trigger.events.clear();
+        // Whoever is no longer overlapping exited
+        for &e in &trigger.overlaps {
+            if !overlaps.contains(&e) {
+                trigger.events.push((e, TriggerEvent::Exit));
+            }
+        }
+        // Whoever wasn't already overlapping entered
+        for &e in &overlaps {
+            let event = match trigger.overlaps.contains(&e) {
+                true => TriggerEvent::Stay,
+                false => TriggerEvent::Enter,
+            };
+            trigger.events.push((e, event));
+        }
+        trigger.overlaps = overlaps;
+    }
+}
+
+/// Categories of colliders, derived from their components. Used to
+/// filter spatial queries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layers(u32);
+
+impl Layers {
+    /// Colliders with a [FixedBody]
+    pub const FIXED: Self = Self(1 << 0);
+    /// Colliders with a [KinematicBody]
+    pub const KINEMATIC: Self = Self(1 << 1);
+    /// Colliders with a [Trigger]
+    pub const TRIGGER: Self = Self(1 << 2);
+    /// Colliders that aren't bodies, ie. shields
+    pub const OTHER: Self = Self(1 << 3);
+    /// Everything
+    pub const ALL: Self = Self(u32::MAX);
+
+    /// Find the layer of an entity
+    pub fn of(entity: EntityRef) -> Self {
+        if entity.has::<Trigger>() {
+            Self::TRIGGER
+        } else if entity.has::<FixedBody>() {
+            Self::FIXED
+        } else if entity.has::<KinematicBody>() {
+            Self::KINEMATIC
+        } else {
+            Self::OTHER
+        }
+    }
+
+    /// Does this contain any of the `other` layers?
+    pub fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+}
+
+impl BitOr for Layers {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl Not for Layers {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        Self(!self.0)
+    }
+}
+
+/// Which entities a spatial query considers.
+#[derive(Debug, Clone, Copy)]
+pub struct QueryFilter {
+    /// Layers to hit
+    pub layers: Layers,
+    /// Entity to ignore, ie. the one making the query
+    pub exclude: Option<Entity>,
+}
+
+impl QueryFilter {
+    /// Creates a filter hitting only `layers`
+    pub fn new(layers: Layers) -> Self {
+        Self { layers, exclude: None }
+    }
+
+    /// Ignore `entity` in the query
+    pub fn exclude(self, entity: Option<Entity>) -> Self {
+        Self { exclude: entity, ..self }
+    }
+
+    /// Is `entity` considered by this filter?
+    fn test(&self, entity: EntityRef) -> bool {
+        self.exclude != Some(entity.entity())
+        && self.layers.intersects(Layers::of(entity))
+    }
+}
+
+impl Default for QueryFilter {
+    fn default() -> Self {
+        Self::new(Layers::ALL)
+    }
+}
+
+/// Result of a [shape_cast]
+#[derive(Debug, Clone, Copy)]
+pub struct ShapeHit {
+    /// Entity hit
+    pub entity: Entity,
+    /// Distance travelled until the hit
+    pub distance: f32,
+    /// Position of the cast shape when it hit
+    pub position: Vec2<f32>,
+    /// Normal of the surface hit, pointing towards the cast shape
+    pub normal: Vec2<f32>,
+}
+
 /// Utility function to send a raycast in the scene and get the entity
 /// hit and at what position
 pub fn raycast(
@@ -232,63 +709,231 @@ pub fn raycast(
     origin: Vec2<f32>,
     dir: Vec2<f32>,
     ignore: Option<Entity>,
+) -> Option<(Entity, Vec2<f32>)> {
+    let filter = QueryFilter::new(!Layers::TRIGGER).exclude(ignore);
+    raycast_filtered(world, origin, dir, filter)
+}
+
+/// Utility function to send a raycast in the scene and get the entity
+/// hit and at what position
+pub fn raycast_solid(
+    world: &World,
+    origin: Vec2<f32>,
+    dir: Vec2<f32>,
+    ignore: Option<Entity>,
+) -> Option<(Entity, Vec2<f32>)> {
+    let filter = QueryFilter::new(Layers::FIXED).exclude(ignore);
+    raycast_filtered(world, origin, dir, filter)
+}
+
+/// Utility function to send a raycast in the scene and get the first entity
+/// passing the filter hit, and at what position.
+pub fn raycast_filtered(
+    world: &World,
+    origin: Vec2<f32>,
+    dir: Vec2<f32>,
+    filter: QueryFilter,
 ) -> Option<(Entity, Vec2<f32>)> {
     let Some(dir) = dir.try_normalize(0.001) else {
         return None;
     };
     let ray = Ray::new(origin.into(), dir);
     // Find min TOI
-    world.query::<(&Transform, &Collider)>()
+    let hit = world.query::<(&Transform, &Collider)>()
         .iter()
+        .filter(|(e, _)| world.entity(*e).is_ok_and(|e| filter.test(e)))
         .filter_map(|(e, (transform, collider))| {
             let toi = collider.cast_ray(
-                &(&*transform).into(),
+                &transform.into(),
                 &ray,
                 std::f32::MAX,
                 true
             )?;
             Some((e, toi))
         })
-        .filter(|(e, _)| ignore != Some(*e))
         .min_by(|(_, x), (_, y)|
             x.partial_cmp(y).unwrap_or(std::cmp::Ordering::Equal)
         )
         .map(|(entity, toi)| (
             entity,
             origin + dir * toi,
+        ));
+    // Misses are drawn as really long rays
+    debug::ray(origin, hit.map_or(origin + dir * 5000.0, |(_, p)| p));
+    hit
+}
+
+/// Utility function to find every entity passing the filter overlapping
+/// a `shape` placed at `position`, ordered from nearest to furthest.
+pub fn overlap_shape(
+    world: &World,
+    shape: &dyn Shape,
+    position: Vec2<f32>,
+    rotation: f32,
+    filter: QueryFilter,
+) -> Vec<Entity> {
+    let iso = Isometry2::new(position, rotation);
+    let mut hits = world.query::<(&Transform, &Collider)>()
+        .iter()
+        .filter(|(e, _)| world.entity(*e).is_ok_and(|e| filter.test(e)))
+        .filter(|(_, (transform, collider))| matches!(
+            query::intersection_test(&iso, shape, &(*transform).into(), (*collider).deref()),
+            Ok(true),
         ))
+        .map(|(e, (transform, _))| (e, (transform.translation - position).norm_squared()))
+        .collect::<Vec<_>>();
+    hits.sort_by(|(_, x), (_, y)|
+        x.partial_cmp(y).unwrap_or(std::cmp::Ordering::Equal)
+    );
+    hits.into_iter()
+        .map(|(e, _)| e)
+        .collect()
 }
 
-/// Utility function to send a raycast in the scene and get the entity
-/// hit and at what position
-pub fn raycast_solid(
+/// Utility function to find every entity passing the filter within
+/// `radius` of `center`, ordered from nearest to furthest.
+pub fn overlap_circle(
     world: &World,
+    center: Vec2<f32>,
+    radius: f32,
+    filter: QueryFilter,
+) -> Vec<Entity> {
+    overlap_shape(world, &Ball::new(radius), center, 0.0, filter)
+}
+
+/// Utility function to sweep a `shape` from `origin` in `dir` for up
+/// to `max_distance`, and get every entity passing the filter it hits,
+/// ordered from first to last hit.
+pub fn shape_cast(
+    world: &World,
+    shape: &dyn Shape,
     origin: Vec2<f32>,
+    rotation: f32,
     dir: Vec2<f32>,
-    ignore: Option<Entity>,
-) -> Option<(Entity, Vec2<f32>)> {
+    max_distance: f32,
+    filter: QueryFilter,
+) -> Vec<ShapeHit> {
     let Some(dir) = dir.try_normalize(0.001) else {
-        return None;
+        return Vec::new();
     };
-    let ray = Ray::new(origin.into(), dir);
-    // Find min TOI
-    world.query::<With<(&Transform, &Collider), &FixedBody>>()
+    let iso = Isometry2::new(origin, rotation);
+    let mut hits = world.query::<(&Transform, &Collider)>()
         .iter()
+        .filter(|(e, _)| world.entity(*e).is_ok_and(|e| filter.test(e)))
         .filter_map(|(e, (transform, collider))| {
-            let toi = collider.cast_ray(
-                &(&*transform).into(),
-                &ray,
-                std::f32::MAX,
-                true
-            )?;
-            Some((e, toi))
+            let toi = query::time_of_impact(
+                &iso,
+                &dir,
+                shape,
+                &transform.into(),
+                &vec2!(0.0, 0.0),
+                collider.deref(),
+                max_distance,
+                true,
+            ).ok()??;
+            Some(ShapeHit {
+                entity: e,
+                distance: toi.toi,
+                position: origin + dir * toi.toi,
+                normal: -(iso.rotation * toi.normal1.into_inner()),
+            })
         })
-        .filter(|(e, _)| ignore != Some(*e))
-        .min_by(|(_, x), (_, y)|
-            x.partial_cmp(y).unwrap_or(std::cmp::Ordering::Equal)
-        )
-        .map(|(entity, toi)| (
-            entity,
-            origin + dir * toi,
+        .collect::<Vec<_>>();
+    hits.sort_by(|x, y|
+        x.distance.partial_cmp(&y.distance).unwrap_or(std::cmp::Ordering::Equal)
+    );
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Spawn a body that stood on a platform moving at `velocity` last
+    /// frame, and is now `gap` above it.
+    fn rider(world: &mut World, velocity: Vec2<f32>, gap: f32) -> Entity {
+        world.spawn((
+            Transform { translation: vec2!(0.0, 0.0), rotation: 0.0 },
+            Collider::rect(100.0, 10.0),
+            FixedBody { velocity },
+        ));
+        let surface = Surface {
+            normal: vec2!(0.0, 1.0),
+            velocity,
+            material: Material::default(),
+        };
+        world.spawn((
+            Transform { translation: vec2!(0.0, 15.0 + gap), rotation: 0.0 },
+            KinematicBody::default(),
+            Collider::rect(20.0, 20.0),
+            Grounded::Yes { time: 1.0, surface },
+            Gravity::default(),
         ))
-}
\ No newline at end of file
+    }
+
+    #[test]
+    fn leaving_a_moving_platform_keeps_its_momentum() {
+        let mut world = World::new();
+        let e = rider(&mut world, vec2!(100.0, 0.0), 10.0);
+        resolve_collisions(&mut world, &Time::default());
+        assert!(matches!(*world.get::<&Grounded>(e).unwrap(), Grounded::No { .. }));
+        assert_eq!(world.get::<&KinematicBody>(e).unwrap().velocity, vec2!(100.0, 0.0));
+    }
+
+    #[test]
+    fn riding_a_moving_platform_keeps_own_velocity() {
+        let mut world = World::new();
+        let e = rider(&mut world, vec2!(100.0, 0.0), -0.5);
+        resolve_collisions(&mut world, &Time::default());
+        assert!(matches!(*world.get::<&Grounded>(e).unwrap(), Grounded::Yes { .. }));
+        assert_eq!(world.get::<&KinematicBody>(e).unwrap().velocity, vec2!(0.0, 0.0));
+    }
+
+    #[test]
+    fn shape_cast_hits_the_nearest_face() {
+        let mut world = World::new();
+        let wall = world.spawn((
+            Transform { translation: vec2!(100.0, 0.0), rotation: 0.0 },
+            Collider::rect(20.0, 100.0),
+            FixedBody::default(),
+        ));
+        let hits = shape_cast(
+            &world,
+            &Ball::new(5.0),
+            vec2!(0.0, 0.0),
+            0.0,
+            vec2!(1.0, 0.0),
+            200.0,
+            QueryFilter::default(),
+        );
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].entity, wall);
+        // Wall's left face is at 90, minus the ball's radius
+        assert!((hits[0].distance - 85.0).abs() < 0.01, "{:?}", hits[0]);
+        assert!((hits[0].normal - vec2!(-1.0, 0.0)).magnitude() < 0.01, "{:?}", hits[0]);
+    }
+
+    #[test]
+    fn shape_cast_respects_the_filter() {
+        let mut world = World::new();
+        let wall = world.spawn((
+            Transform { translation: vec2!(100.0, 0.0), rotation: 0.0 },
+            Collider::rect(20.0, 100.0),
+            FixedBody::default(),
+        ));
+        let cast = |max_distance, filter| shape_cast(
+            &world,
+            &Ball::new(5.0),
+            vec2!(0.0, 0.0),
+            0.0,
+            vec2!(1.0, 0.0),
+            max_distance,
+            filter,
+        );
+        assert_eq!(cast(200.0, QueryFilter::new(Layers::FIXED)).len(), 1);
+        assert!(cast(200.0, QueryFilter::new(Layers::TRIGGER)).is_empty());
+        assert!(cast(200.0, QueryFilter::default().exclude(Some(wall))).is_empty());
+        // Out of range
+        assert!(cast(50.0, QueryFilter::default()).is_empty());
+    }
+}
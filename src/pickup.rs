@@ -0,0 +1,224 @@
+//! Pickups placed around levels, handing out health and timed boosts to
+//! the first player to reach them.
+
+use hecs::{ Entity, World };
+use serde::Deserialize;
+
+use crate::{
+    math::Vec2,
+    physics::{ Collider, Trigger },
+    render::{ Sprite, Costume, Visibility },
+    transform::{ Transform, Parent, LocalPosition },
+    platform::{ Socket, Time, Connection },
+    network::Packet,
+    player::Player,
+    health::Health,
+    ability::{ Ability, Cooldown },
+    bullet::TimeToLive,
+    timescale::TimeScale,
+};
+
+/// Size of a pickup's trigger.
+const PICKUP_SIZE: f32 = 40.0;
+/// Hitpoints restored by a health pack.
+const HEAL: f32 = 50.0;
+/// Seconds a boost lasts.
+const BOOST_DURATION: f32 = 10.0;
+/// Damage multiplier while boosted.
+const DAMAGE_BOOST: f32 = 1.5;
+/// Movement speed multiplier while boosted.
+const SPEED_BOOST: f32 = 1.4;
+
+/// What a pickup does to whoever collects it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[repr(u32)]
+pub enum PickupKind {
+    /// Restores some health.
+    Health,
+    /// Deal more damage for a while.
+    DamageBoost,
+    /// Move faster for a while.
+    SpeedBoost,
+    /// Every ability is ready again.
+    CooldownReset,
+}
+
+/// Component for [Trigger]s handing out a pickup to the first player
+/// overlapping them.
+#[derive(Debug, Clone)]
+pub struct Pickup {
+    pub kind: PickupKind,
+    /// Seconds before coming back after being taken.
+    pub respawn: f32,
+    /// Seconds left before coming back, if taken.
+    taken: Option<f32>,
+}
+
+/// Component for a player's timed boosts, in seconds left of each.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Boosts {
+    pub damage: f32,
+    pub speed: f32,
+}
+
+impl Boosts {
+    /// Multiplier on damage dealt.
+    pub fn damage_factor(&self) -> f32 {
+        if self.damage > 0.0 { DAMAGE_BOOST } else { 1.0 }
+    }
+
+    /// Multiplier on movement speed.
+    pub fn speed_factor(&self) -> f32 {
+        if self.speed > 0.0 { SPEED_BOOST } else { 1.0 }
+    }
+}
+
+/// A pickup of `kind` at `pos`, coming back `respawn` seconds after
+/// being taken.
+pub fn pickup(world: &mut World, e: Entity, pos: Vec2<f32>, kind: PickupKind, respawn: f32) {
+    world.spawn_at(e, (
+        Collider::rect(PICKUP_SIZE, PICKUP_SIZE),
+        Trigger::default(),
+        Pickup { kind, respawn, taken: None },
+        Sprite::new(Costume::Pickup { position: pos, kind }),
+        Transform {
+            translation: pos,
+            rotation: 0.0,
+        },
+    ));
+}
+
+/// Show or hide a pickup's sprite.
+fn set_visibility(world: &mut World, e: Entity, visibility: Visibility) {
+    if let Ok(mut sprite) = world.get::<&mut Sprite>(e) {
+        sprite.visibility = visibility;
+    }
+}
+
+/// Give `player` what a pickup of `kind` does.
+fn apply(world: &mut World, socket: &Socket, player: Entity, kind: PickupKind) {
+    match kind {
+        PickupKind::Health => {
+            let Ok(mut health) = world.get::<&mut Health>(player) else {
+                return;
+            };
+            health.now = (health.now + HEAL).min(health.max);
+            socket.broadcast(&Packet::EntityHealth(player, health.now));
+        },
+        PickupKind::DamageBoost | PickupKind::SpeedBoost => {
+            let Ok(mut boosts) = world.get::<&mut Boosts>(player) else {
+                return;
+            };
+            match kind {
+                PickupKind::DamageBoost => boosts.damage = BOOST_DURATION,
+                _ => boosts.speed = BOOST_DURATION,
+            }
+            socket.broadcast(&Packet::PlayerBoost {
+                player,
+                kind,
+                duration: BOOST_DURATION,
+            });
+        },
+        PickupKind::CooldownReset => {
+            let mut bindings = Vec::new();
+            for (_, (ability, cooldown)) in world.query_mut::<(&Ability, &mut Cooldown)>() {
+                if ability.owner == player {
+                    cooldown.0 = 0.0;
+                    bindings.push(ability.binding);
+                }
+            }
+            let Ok(id) = world.get::<&Connection>(player) else {
+                return;
+            };
+            for binding in bindings {
+                socket.send(*id, &Packet::CooldownStart { binding, duration: 0.0 });
+            }
+        },
+    }
+}
+
+/// System that hands out [Pickup]s to players overlapping them, brings
+/// them back after a while and wears off [Boosts].
+pub fn pickups(world: &mut World, socket: &Socket, time: &Time) {
+    if cfg!(client) {
+        for (_, packet) in socket.packets() {
+            match *packet {
+                Packet::PickupTaken(e) => set_visibility(world, e, Visibility::Hidden),
+                Packet::PickupRestored(e) => set_visibility(world, e, Visibility::Shown),
+                Packet::PlayerBoost { player, kind, duration } => {
+                    world.spawn((
+                        Parent(player),
+                        LocalPosition(Default::default()),
+                        Transform::default(),
+                        Sprite::new(Costume::Boost {
+                            position: Default::default(),
+                            kind,
+                        }),
+                        TimeToLive::Seconds(duration),
+                    ));
+                },
+                _ => {},
+            }
+        }
+        // Boosts follow their player
+        for (_, (transform, sprite)) in world.query_mut::<(&Transform, &mut Sprite)>() {
+            if let Costume::Boost { position, .. } = &mut sprite.costume {
+                *position = transform.translation;
+            }
+        }
+        return;
+    }
+    // Wear off, in real time like time modifiers
+    for (_, boosts) in world.query_mut::<&mut Boosts>() {
+        boosts.damage = (boosts.damage - time.dt()).max(0.0);
+        boosts.speed = (boosts.speed - time.dt()).max(0.0);
+    }
+    // Respawn
+    let mut restored = Vec::new();
+    for (e, (pickup, scale)) in world.query_mut::<(&mut Pickup, Option<&TimeScale>)>() {
+        let Some(time_left) = &mut pickup.taken else {
+            continue;
+        };
+        *time_left -= time.dt() * scale.map(|s| s.0).unwrap_or(1.0);
+        if *time_left <= 0.0 {
+            pickup.taken = None;
+            restored.push(e);
+        }
+    }
+    for e in restored {
+        set_visibility(world, e, Visibility::Shown);
+        socket.broadcast(&Packet::PickupRestored(e));
+    }
+    // Collect
+    let mut collected = Vec::new();
+    for (e, (trigger, pickup)) in &mut world.query::<(&Trigger, &mut Pickup)>() {
+        if pickup.taken.is_some() {
+            continue;
+        }
+        // Overlaps rather than enter events, someone might be waiting on it
+        let player = trigger
+            .overlaps()
+            .copied()
+            .find(|&e2| matches!(world.satisfies::<&Player>(e2), Ok(true)));
+        let Some(player) = player else {
+            continue;
+        };
+        pickup.taken = Some(pickup.respawn);
+        collected.push((e, player, pickup.kind));
+    }
+    for (e, player, kind) in collected {
+        set_visibility(world, e, Visibility::Hidden);
+        socket.broadcast(&Packet::PickupTaken(e));
+        apply(world, socket, player, kind);
+    }
+    // Newcomers need to know what's already taken
+    // TODO: reliable transport
+    for &connection in socket.connections() {
+        for (e, pickup) in world.query_mut::<&Pickup>() {
+            if pickup.taken.is_some() {
+                socket.send(connection, &Packet::PickupTaken(e));
+            }
+        }
+    }
+}
@@ -2,10 +2,11 @@
 //! some WebAssembly runtime.
 
 use std::mem::{ MaybeUninit, self };
-use std::ffi::{ CString, c_char };
+use std::ffi::{ CString, CStr, c_char };
 use once_cell::unsync::OnceCell;
 
-use crate::ability::AbilityKind;
+use crate::math::Vec2;
+use crate::debug::Line;
 use crate::render::{Sprite, Visibility};
 use crate::{
     network::Packet,
@@ -36,7 +37,8 @@ extern {
     // Quick n dirty, this is invoked in nodejs only(ie. server)
     fn net_poll_joins(
         who: *mut MaybeUninit<Connection>,
-        ptr: *mut MaybeUninit<[AbilityKind; 4]>
+        ptr: *mut c_char,
+        len: usize
     ) -> bool;
 
     // SAFETY:
@@ -45,7 +47,11 @@ extern {
     fn render_new_sprite(ptr: *const Costume) -> u32;
     fn render_update_sprite(handle: u32, ptr: *const Costume, visibility: Visibility);
     fn render_drop_sprite(handle: u32);
+    fn render_add_sprite_polygon(handle: u32, ptr: *const Vec2<f32>, len: usize);
     fn render_set_cooldown(binding: usize, time_left: f32);
+    fn render_debug_lines(ptr: *const Line, len: usize);
+    fn render_set_bounds(min_x: f32, min_y: f32, max_x: f32, max_y: f32);
+    fn render_add_kill(victim: usize, killer: usize, cause: *const c_char);
 
     fn input_get_dx() -> f32;
     fn input_get_dy() -> f32;
@@ -54,11 +60,12 @@ extern {
     fn input_get_fire() -> bool;
     fn input_get_ability(i: usize) -> bool;
     fn input_set_player_position(x: f32, y: f32);
+    fn input_get_debug() -> bool;
 
     fn time_now() -> u32;
 }
 
-#[no_mangle]
+#[cfg_attr(not(test), no_mangle)]
 extern "C" fn main() {
     Logger::hook();
 
@@ -141,8 +148,8 @@ pub struct Socket {
     connections: Vec<Connection>,
     /// Buffered new disconnections.
     disconnections: Vec<Connection>,
-    /// Players to spawn.
-    joins: Vec<(Connection, [AbilityKind; 4])>,
+    /// Players to spawn, with their deck of comma separated ability ids.
+    joins: Vec<(Connection, String)>,
 }
 
 /// Unique identifier for a networked connection.
@@ -203,13 +210,18 @@ impl Socket {
             });
         }
         // Player spawns
-        let mut deck = MaybeUninit::uninit();
-        while unsafe { net_poll_joins(&mut conn as _, &mut deck as _) } {
-            self.joins.push(unsafe {
+        let mut deck = [0 as c_char; 256];
+        while unsafe { net_poll_joins(&mut conn as _, deck.as_mut_ptr(), deck.len()) } {
+            let deck = unsafe {
                 // SAFETY:
-                // Poll will return true iff initialized.
-                (conn.assume_init(), deck.assume_init())
-            })
+                // Poll will return true iff initialized, with a null
+                // terminated string that fits.
+                CStr::from_ptr(deck.as_ptr())
+            };
+            self.joins.push((
+                unsafe { conn.assume_init() },
+                deck.to_string_lossy().into_owned(),
+            ));
         }
     }
 
@@ -229,7 +241,7 @@ impl Socket {
     }
 
     /// Iterate clients that have hit "join"
-    pub fn joins(&self) -> impl Iterator<Item = &(Connection, [AbilityKind; 4])> {
+    pub fn joins(&self) -> impl Iterator<Item = &(Connection, String)> {
         self.joins.iter()
     }
 }
@@ -246,9 +258,15 @@ impl Canvas {
                 render_update_sprite(handle, &sprite.costume as _, sprite.visibility);
             }
         } else {
-            sprite.handle = Some(unsafe {
+            let handle = unsafe {
                 render_new_sprite(&sprite.costume as _)
-            });
+            };
+            for polygon in &sprite.outline {
+                unsafe {
+                    render_add_sprite_polygon(handle, polygon.as_ptr(), polygon.len());
+                }
+            }
+            sprite.handle = Some(handle);
         }
     }
 
@@ -267,6 +285,31 @@ impl Canvas {
             render_set_cooldown(binding, time_left);
         }
     }
+
+    /// Show the level's bounds, from `min` to `max`.
+    pub fn set_bounds(&self, min: Vec2<f32>, max: Vec2<f32>) {
+        unsafe {
+            render_set_bounds(min.x, min.y, max.x, max.y);
+        }
+    }
+
+    /// Add an entry to the kill feed, players are told apart by color.
+    /// Killed by `killer` if any, otherwise by `cause`.
+    pub fn add_kill(&self, victim: usize, killer: Option<usize>, cause: &str) {
+        let Ok(cause) = CString::new(cause) else {
+            return;
+        };
+        unsafe {
+            render_add_kill(victim, killer.unwrap_or(usize::MAX), cause.as_ptr());
+        }
+    }
+
+    /// Replace the debug overlay with `lines`.
+    pub fn draw_debug(&self, lines: &[Line]) {
+        unsafe {
+            render_debug_lines(lines.as_ptr(), lines.len());
+        }
+    }
 }
 
 /// Abstraction over keyboard/controller input.
@@ -316,6 +359,13 @@ impl Gamepad {
         }
     }
 
+    /// Get whether the debug overlay was toggled since last time
+    pub fn debug(&self) -> bool {
+        unsafe {
+            input_get_debug()
+        }
+    }
+
     /// Used to emulate 2nd joystick via mouse controls.
     pub fn set_player_position(&self, x: f32, y: f32) {
         unsafe {
@@ -325,6 +375,7 @@ impl Gamepad {
 }
 
 /// Abstraction over time measurements.
+#[derive(Default)]
 pub struct Time {
     /// Start time, in ms
     start: u32,
@@ -332,19 +383,6 @@ pub struct Time {
     now: u32,
     /// Second most recent time polled
     last: Option<u32>,
-    /// Time scale multiplier.
-    pub scale: f32,
-}
-
-impl Default for Time {
-    fn default() -> Self {
-        Self {
-            start: Default::default(),
-            now: Default::default(),
-            last: Default::default(),
-            scale: 1.0,
-        }
-    }
 }
 
 impl Time {
@@ -370,7 +408,7 @@ impl Time {
 
     /// Seconds between this frame and the one before.
     pub fn dt(&self) -> f32 {
-        self.scale * self.dt_ms() as f32 / 1000.0
+        self.dt_ms() as f32 / 1000.0
     }
 
     /// Milliseconds between this frame and the one before.
@@ -36,18 +36,7 @@ export type Packet = void;
 /** Unique identifier for a channel. */
 export type Connection = number;
 
-export enum AbilityKind {
-    Shotgun,
-    AssaultRifle,
-    DualGun,
-    Shield,
-    Push,
-    Freeze,
-    Lightning,
-    BubbleShield,
-    Heal,
-}
-
+/** Tags of `render::Costume`, must stay in the same order. */
 export enum Costume {
     Player,
     Bullet,
@@ -63,7 +52,35 @@ export enum Costume {
     Heal,
     SpawnIn,
     Shadow,
-    Platform
+    Platform,
+    Shape,
+    Hazard,
+    JumpPad,
+    Teleporter,
+    Launch,
+    Warp,
+    Debris,
+    Pickup,
+    Sniper,
+    Tracer,
+    Rope,
+    Boost,
+}
+export enum PickupKind {
+    Health,
+    DamageBoost,
+    SpeedBoost,
+    CooldownReset,
+}
+/** Opaque type representing a debug overlay line segment. */
+export type DebugLine = void;
+export enum DebugColor {
+    Fixed,
+    Kinematic,
+    Trigger,
+    Other,
+    Contact,
+    Ray,
 }
 export enum Visibility {
     Shown,
@@ -85,12 +102,17 @@ export interface Imports extends WebAssembly.ModuleImports {
     net_poll_disconnections(ptr: RefMut<Uninit<Connection>>): boolean;
     net_poll_joins(
         who: RefMut<Uninit<Connection>>,
-        ptr: RefMut<Uninit<AbilityKind[]>>
+        ptr: RefMut<Uninit<u8[]>>,
+        len: usize
     ): boolean;
     render_new_sprite(ptr: Ref<Costume>): u32;
     render_update_sprite(handle: u32, ptr: Ref<Costume>, visibility: Visibility);
     render_drop_sprite(handle: u32);
+    render_add_sprite_polygon(handle: u32, ptr: Ref<f32[]>, len: usize);
     render_set_cooldown(binding: usize, time_left: f32);
+    render_debug_lines(ptr: Ref<DebugLine[]>, len: usize);
+    render_set_bounds(min_x: f32, min_y: f32, max_x: f32, max_y: f32);
+    render_add_kill(victim: usize, killer: usize, cause: Ref<u8>);
 
     input_get_dx(): f32;
     input_get_dy(): f32;
@@ -99,6 +121,7 @@ export interface Imports extends WebAssembly.ModuleImports {
     input_get_fire(): boolean;
     input_get_ability(i: usize): boolean;
     input_set_player_position(x: f32, y: f32): void;
+    input_get_debug(): boolean;
 
     time_now(): u32;
     now(): f64;
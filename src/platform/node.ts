@@ -6,7 +6,7 @@ import {
     Memory, Ref, RefMut, Uninit,
     cstring,
     Packet, Connection,
-    AbilityKind, Costume, Visibility,
+    Costume, Visibility, DebugLine,
     usize, u32, f32, u8, f64,
     instantiate,
 } from "./mod";
@@ -96,23 +96,6 @@ module Net {
                 }
             }
         }
-        /** Parse ability kinds */
-        function abilityKind(ability: string): AbilityKind {
-            switch (ability) {
-                case "shotgun": return AbilityKind.Shotgun;
-                case "assault-rifle": return AbilityKind.AssaultRifle;
-                case "dual-gun": return AbilityKind.DualGun;
-                case "shield": return AbilityKind.Shield;
-                case "push": return AbilityKind.Push;
-                case "time-freeze": return AbilityKind.Freeze;
-                case "lightning": return AbilityKind.Lightning;
-                case "bubble-shield": return AbilityKind.BubbleShield;
-                case "heal": return AbilityKind.Heal;
-                default:
-                    // These come from DB, client can't crash the server
-                    throw `Unknown ability kind "${ability}"`;
-            }
-        }
         return {
             net_emit(to: Connection, ptr: Ref<Packet>, len: usize): void {
                 if (!clients.hasOwnProperty(to)) {
@@ -175,7 +158,8 @@ module Net {
             },
             net_poll_joins(
                 who: RefMut<Uninit<Connection>>,
-                ptr: RefMut<Uninit<AbilityKind[]>>
+                ptr: RefMut<Uninit<u8[]>>,
+                len: usize
             ): boolean {
                 if (!joins.length) {
                     return false;
@@ -183,17 +167,17 @@ module Net {
                 const { uuid, deck } = joins.shift()!;
 
                 const id = findClient(uuid);
-                const abilities = deck.map((c) => abilityKind(c));
                 if (id === undefined) {
                     return false;
                 }
-                if (abilities.length != 4) {
-                    throw "Invalid deck!";
-                }
+                // Ability ids are checked by the game
+                const abilities = new TextEncoder().encode(deck.join(","));
                 // SAFETY:
-                // Caller guarentees the pointer is of correct size.
+                // Caller guarentees the pointers are of correct size.
                 new Uint32Array(mem().buffer, who).set([id]);
-                new Uint32Array(mem().buffer, ptr).set(abilities);
+                const buffer = new Uint8Array(mem().buffer, ptr, len);
+                buffer.set(abilities.subarray(0, len - 1));
+                buffer[Math.min(abilities.length, len - 1)] = 0;
 
                 return true;
             }
@@ -214,8 +198,20 @@ module Render {
             render_drop_sprite(handle: u32) {
                 // Node is headless!
             },
+            render_add_sprite_polygon(handle: u32, ptr: Ref<f32[]>, len: usize) {
+                // Node is headless!
+            },
             render_set_cooldown(binding: usize, time_left: f32) {
                 // Node is headless!
+            },
+            render_debug_lines(ptr: Ref<DebugLine[]>, len: usize) {
+                // Node is headless!
+            },
+            render_set_bounds(min_x: f32, min_y: f32, max_x: f32, max_y: f32) {
+                // Node is headless!
+            },
+            render_add_kill(victim: usize, killer: usize, cause: Ref<u8>) {
+                // Node is headless!
             }
         }
     }
@@ -250,6 +246,10 @@ module Input {
             },
             input_set_player_position(x: f32, y: f32): void {
                 return;
+            },
+            input_get_debug(): boolean {
+                // Node is headless!
+                return false;
             }
         }
     }
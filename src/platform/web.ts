@@ -3,13 +3,13 @@
  */
 import { StateUpdater } from "preact/hooks";
 import { geckos, RawMessage, ClientChannel, ChannelId } from "@geckos.io/client";
-import { Shape, Svg, SVG } from "@svgdotjs/svg.js";
+import { Line, Shape, Svg, SVG } from "@svgdotjs/svg.js";
 import "@svgdotjs/svg.filter.js";
 import {
     Memory, Ref, RefMut, Uninit,
     cstring,
     Packet, Connection,
-    AbilityKind, Costume, Visibility,
+    Costume, Visibility, DebugLine, DebugColor, PickupKind,
     usize, u32, f32, u8, f64,
     instantiate,
 } from "./mod";
@@ -18,10 +18,11 @@ export async function game(port: number) {
     const draw = SVG();
     const channel = geckos({ port });
     const setCooldowns = {};
+    const setKills = {};
     const wasm = await instantiate({
         ...Log.imports(() => wasm.memory),
         ...Net.imports(() => wasm.memory, channel),
-        ...Render.imports(() => wasm.memory, draw, setCooldowns),
+        ...Render.imports(() => wasm.memory, draw, setCooldowns, setKills),
         ...Input.imports(),
         ...Time.imports(),
     });
@@ -31,9 +32,10 @@ export async function game(port: number) {
         wasm.tick();
     });
     return {
-        hook(node: HTMLElement, cb: StateUpdater<number[]>): void {
+        hook(node: HTMLElement, cb: StateUpdater<number[]>, kills: StateUpdater<Kill[]>): void {
             draw.addTo(node);
             setCooldowns["current"] = cb;
+            setKills["current"] = kills;
         },
         uuid(): ChannelId {
             return channel.id;
@@ -147,7 +149,8 @@ module Net {
             },
             net_poll_joins(
                 who: RefMut<Uninit<Connection>>,
-                ptr: RefMut<Uninit<AbilityKind[]>>
+                ptr: RefMut<Uninit<u8[]>>,
+                len: usize
             ): boolean {
                 // Node.js only
                 return false;
@@ -156,8 +159,26 @@ module Net {
     }
 }
 
+/** Entry of the kill feed, players are told apart by color. */
+export type Kill = {
+    id: number,
+    victim: string,
+    /** Color of the killer, if killed by a player. */
+    killer?: string,
+    /** What killed the victim otherwise. */
+    cause: string,
+};
+
+/** Seconds kills stay in the feed. */
+const KILL_FEED_TIME = 5;
+
 module Render {
-    export function imports(mem: () => Memory, root: Svg, setCooldowns: { current?: StateUpdater<number[]> }) {
+    export function imports(
+        mem: () => Memory,
+        root: Svg,
+        setCooldowns: { current?: StateUpdater<number[]> },
+        setKills: { current?: StateUpdater<Kill[]> },
+    ) {
         const draw = root.size("200%", "100%")
             .addClass("cartesian")
             .group()
@@ -231,6 +252,29 @@ module Render {
         }
         const shadows = draw.group().back();
         const platforms = draw.group().after(shadows);
+        // Outline of the level's bounds
+        // TODO: clamp a camera to these, once the view isn't fixed
+        const bounds = draw.rect(0, 0)
+            .fill("none")
+            .stroke({ color: "#ECF0F1", width: 2, dasharray: "10 10", opacity: 0.3 })
+            .after(platforms);
+        const debug = draw.group();
+        const DEBUG_COLORS = {
+            [DebugColor.Fixed]: "#2ECC71",
+            [DebugColor.Kinematic]: "#3498DB",
+            [DebugColor.Trigger]: "#F1C40F",
+            [DebugColor.Other]: "#ECF0F1",
+            [DebugColor.Contact]: "#E74C3C",
+            [DebugColor.Ray]: "#E67E22",
+        };
+        const PICKUP_COLORS = {
+            [PickupKind.Health]: "#2ECC71",
+            [PickupKind.DamageBoost]: "#E74C3C",
+            [PickupKind.SpeedBoost]: "#3498DB",
+            [PickupKind.CooldownReset]: "#F1C40F",
+        };
+        // Kills ever added, for unique ids
+        let kills = 0;
         const COLORS = [
             "#EFC643",
             "#27AE60",
@@ -295,6 +339,7 @@ module Render {
                                     .height(40)
                                 );
                         case Costume.Push:
+                            const [, , radius] = costume(ptr)[1];
                             const a = draw
                                 .circle(1)
                                 .fill("none")
@@ -305,8 +350,8 @@ module Render {
                                     dasharray: "5"
                                 })
                             a.animate(500, 0, "now")
-                                .width(3000)
-                                .height(3000);
+                                .width(radius * 2)
+                                .height(radius * 2);
                             screenShake(20, 20, 0.9);
                                 
                             return a;
@@ -376,6 +421,107 @@ module Render {
                                     .image("assets/weapons/platform.svg")
                                     .scale(1.0, -3.0)
                                 );
+                        case Costume.Shape:
+                            // Polygons are added by `render_add_sprite_polygon`
+                            return draw
+                                .group()
+                                .addTo(platforms);
+                        case Costume.Hazard:
+                            return draw
+                                .rect(0, 0)
+                                .fill("#E74C3C")
+                                .opacity(0.6)
+                                .addTo(platforms);
+                        case Costume.JumpPad:
+                            return draw
+                                .rect(0, 10)
+                                .fill("#F1C40F")
+                                .addTo(platforms);
+                        case Costume.Teleporter:
+                            return draw
+                                .ellipse(50, 70)
+                                .fill("none")
+                                .stroke({ color: "#9B59B6", width: 4 })
+                                .addTo(platforms);
+                        case Costume.Launch:
+                            const launch = draw
+                                .polyline([[-20, 0], [0, 30], [20, 0]])
+                                .fill("none")
+                                .stroke({ color: "#F1C40F", width: 4 });
+                            launch.animate(300, 0, "now")
+                                .dy(60)
+                                .attr({ opacity: 0 });
+                            return draw.group().add(launch);
+                        case Costume.Warp:
+                            const warp = draw
+                                .circle(10)
+                                .fill("#9B59B6")
+                                .opacity(0.8);
+                            warp.animate(300, 0, "now")
+                                .size(120, 120)
+                                .attr({ opacity: 0 });
+                            return draw.group().add(warp);
+                        case Costume.Debris:
+                            // Pieces fly apart and fade, spread over the platform
+                            const [x, y, width] = costume(ptr)[1];
+                            const debris = draw.group();
+                            for (let i = 0; i < 8; i++) {
+                                const piece = draw
+                                    .rect(width / 8, 12)
+                                    .fill("#34495E")
+                                    .center(x - width / 2 + (i + 0.5) * width / 8, y)
+                                    .addTo(debris);
+                                piece.animate(600, 0, "now")
+                                    .dmove(40 * (Math.random() - 0.5), -60 - 60 * Math.random())
+                                    .rotate(180 * (Math.random() - 0.5))
+                                    .attr({ opacity: 0 });
+                            }
+                            return debris;
+                        case Costume.Sniper:
+                            // Sight first, so the barrel is drawn on top
+                            return draw
+                                .group()
+                                .add(draw
+                                    .line(0, 0, 0, 0)
+                                    .stroke({ color: "#E74C3C", width: 1, opacity: 0.7 })
+                                )
+                                .add(draw.rect(70, 6).fill("#2C3E50").y(-3))
+                                .add(draw.rect(20, 8).fill("#2C3E50").move(10, 3));
+                        case Costume.Tracer:
+                            const [fx, fy, tx, ty] = costume(ptr)[1];
+                            const tracer = draw
+                                .line(fx, fy, tx, ty)
+                                .stroke({ color: "#ECF0F1", width: 3 });
+                            tracer.animate(300, 0, "now")
+                                .attr({ opacity: 0, "stroke-width": 0 });
+                            return draw.group().add(tracer);
+                        case Costume.Rope:
+                            return draw
+                                .line(0, 0, 0, 0)
+                                .stroke({ color: "#7F8C8D", width: 2 });
+                        case Costume.Pickup:
+                            // Kind comes after the position
+                            const pickup = draw
+                                .circle(30)
+                                .fill(PICKUP_COLORS[new Uint32Array(mem().buffer, ptr + 4)[2]])
+                                .stroke({ color: "#ECF0F1", width: 3 });
+                            pickup.animate(800, 0, "now")
+                                .loop(0, true)
+                                .attr({ opacity: 0.6 });
+                            return draw.group().add(pickup);
+                        case Costume.Boost:
+                            const boost = draw
+                                .ellipse(60, 80)
+                                .fill("none")
+                                .stroke({
+                                    color: PICKUP_COLORS[new Uint32Array(mem().buffer, ptr + 4)[2]],
+                                    width: 3,
+                                    dasharray: "8",
+                                });
+                            boost.animate(400, 0, "now")
+                                .loop(0, true)
+                                .attr({ opacity: 0.3 });
+                            return draw.group().add(boost);
                     }
                 };
                 return cache.add(element());
@@ -420,6 +566,20 @@ module Render {
                             scaleY: Math.abs(args[2]) > Math.PI / 2 ? -1 : 1,
                         });
                 }
+                // Sniper, pivots on the player with its sight
+                if (tag == Costume.Sniper) {
+                    (element.first() as Line).plot(0, 0, args[3], 0);
+                    element.transform({
+                        translate: [args[0], args[1]],
+                        rotate: 180 / Math.PI * args[2],
+                        scaleY: Math.abs(args[2]) > Math.PI / 2 ? -1 : 1,
+                        origin: [0, 0],
+                    });
+                }
+                // Rope
+                if (tag == Costume.Rope) {
+                    (element as Line).plot(args[0], args[1], args[2], args[3]);
+                }
                 // Player
                 if (tag == Costume.Player) {
                     element.transform({
@@ -466,6 +626,40 @@ module Render {
                         })
                         // .width(args[2]);
                 }
+                // Hazard
+                if (tag == Costume.Hazard) {
+                    element
+                        .size(args[2], args[3])
+                        .center(args[0], args[1]);
+                }
+                // Gadgets
+                if (tag == Costume.JumpPad) {
+                    element
+                        .width(args[2])
+                        .center(args[0], args[1]);
+                }
+                if ([Costume.Teleporter, Costume.Launch, Costume.Warp, Costume.Pickup, Costume.Boost].includes(tag)) {
+                    element.center(args[0], args[1]);
+                }
+                // Shape
+                if (tag == Costume.Shape) {
+                    element.transform({
+                        translate: [args[0], args[1]],
+                        rotate: 180 / Math.PI * args[2],
+                        origin: [0, 0],
+                    });
+                }
+            },
+            render_add_sprite_polygon(handle: u32, ptr: Ref<f32[]>, len: usize) {
+                const xy = new Float32Array(mem().buffer, ptr, len * 2);
+                const points: [number, number][] = [];
+                for (let i = 0; i < len; i++) {
+                    points.push([xy[2 * i], xy[2 * i + 1]]);
+                }
+                cache.get(handle).add(draw
+                    .polygon(points)
+                    .fill("#34495E")
+                );
             },
             render_drop_sprite(handle: u32) {
                 // Remove from DOM
@@ -483,6 +677,36 @@ module Render {
                         i === binding ? 0 : t
                     )));
                 }, time_left)
+            },
+            render_set_bounds(min_x: f32, min_y: f32, max_x: f32, max_y: f32) {
+                bounds
+                    .size(max_x - min_x, max_y - min_y)
+                    .move(min_x, min_y);
+            },
+            render_add_kill(victim: usize, killer: usize, cause: Ref<u8>) {
+                const kill: Kill = {
+                    id: kills++,
+                    victim: COLORS[victim % COLORS.length],
+                    // usize::MAX when there's no killer, as a signed i32 or not
+                    killer: (killer >>> 0) !== 0xFFFFFFFF ? COLORS[killer % COLORS.length] : undefined,
+                    cause: cstring(mem(), cause),
+                };
+                setKills.current?.((prev) => [...prev, kill]);
+                setTimeout(() => {
+                    setKills.current?.((prev) => prev.filter(({ id }) => id !== kill.id));
+                }, KILL_FEED_TIME * 1000);
+            },
+            render_debug_lines(ptr: Ref<DebugLine[]>, len: usize) {
+                // Redrawn from scratch every frame, on top of everything
+                debug.clear().front();
+                // Line is { from: (f32, f32), to: (f32, f32), color: u32 }
+                const xy = new Float32Array(mem().buffer, ptr, len * 5);
+                const color = new Uint32Array(mem().buffer, ptr, len * 5);
+                for (let i = 0; i < len; i++) {
+                    const [x1, y1, x2, y2] = xy.subarray(5 * i, 5 * i + 4);
+                    debug.line(x1, y1, x2, y2)
+                        .stroke({ color: DEBUG_COLORS[color[5 * i + 4]], width: 2 });
+                }
             }
         }
     }
@@ -530,7 +754,12 @@ module Input {
 
             return { x: x / v, y: y / v };
         }
+        // Debug overlay toggles on every press
+        let debug = false;
         document.addEventListener("keydown", (e) => {
+            if (e.key === "`" && !e.repeat) {
+                debug = true;
+            }
             buf[e.key] = e.timeStamp;
         });
         document.addEventListener("keyup", (e) => {
@@ -580,6 +809,11 @@ module Input {
                 // if (controls is joystick) return
                 origin.x = x;
                 origin.y = y;
+            },
+            input_get_debug(): boolean {
+                const toggled = debug;
+                debug = false;
+                return toggled;
             }
         }
     }
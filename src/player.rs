@@ -1,15 +1,17 @@
 use hecs::{ World, EntityBuilder, Entity };
 
 use crate::{
-    physics::{ KinematicBody, Grounded, Collider, Gravity, self },
+    physics::{ KinematicBody, Grounded, Walls, Collider, Gravity, DropThrough, Material, self },
     input::{Input, LookDirection},
     platform::{ Socket, Time, Connection },
     render::{ Sprite, Costume, Shadow },
     transform::{ Transform, NetworkPosition, Parent },
-    math::vec2,
+    math::{ Vec2, vec2 },
     network::Packet,
-    ability::{ AbilityKind, self, Ability, TimeScale },
+    ability::{ AbilityKind, self, Ability },
+    timescale::TimeScale, spawn,
     health::{ Health, self }, bullet::TimeToLive,
+    pickup::Boosts,
 };
 
 /// Component that marks an entity as a player.
@@ -18,13 +20,20 @@ pub struct Player {
     color: usize,
 }
 
+impl Player {
+    /// Index of the player's color.
+    pub fn color(&self) -> usize {
+        self.color
+    }
+}
+
 /// Prefab for a player entity
-fn prefab(deck: [AbilityKind; 4], color: usize) -> EntityBuilder {
+fn prefab(deck: [AbilityKind; 4], color: usize, position: Vec2<f32>) -> EntityBuilder {
     let mut builder = EntityBuilder::new();
     builder.add_bundle((
         Player { deck, color },
         Sprite::new(Costume::Player {
-            position: vec2!(0.0, 500.0),
+            position,
             scale: vec2!(1.0, 1.0),
             lean: 0.0,
             color: color as _,
@@ -32,17 +41,22 @@ fn prefab(deck: [AbilityKind; 4], color: usize) -> EntityBuilder {
         Health {
             now: 100.0,
             max: 100.0,
+            last_hit: None,
         },
         Input::default(),
-        Collider::rect(30.0, 50.0),
+        Collider::capsule(30.0, 50.0),
         Grounded::default(),
-        Gravity { acceleration: vec2!(0.0, -2500.0) },
+        Walls::default(),
+        DropThrough::default(),
+        Gravity { acceleration: vec2!(0.0, -GRAVITY) },
         Transform {
-            translation: vec2!(100.0, 500.0),
+            translation: position,
             rotation: 0.0,
         },
         NetworkPosition::default(),
         LookDirection::default(),
+        TimeScale::default(),
+        Boosts::default(),
     ));
     if cfg!(server) {
         builder.add(KinematicBody::default());
@@ -59,34 +73,42 @@ pub fn networked_instantiate(
     if cfg!(server) {
         // Server spawns player for every connection
         for (connection, deck) in socket.joins() {
+            // Decks come from the DB, clients can't crash the server
+            let Some(deck) = ability::parse_deck(deck) else {
+                log::warn!("{connection:?} joined with invalid deck \"{deck}\".");
+                continue;
+            };
             let e = reserved.next().unwrap_or_else(|| world.reserve_entity());
             let color = e.id() as usize;
+            let position = spawn::select(world, e);
             // Player
-            world.spawn_at(e, prefab(*deck, color).add(*connection).build());
+            world.spawn_at(e, prefab(deck, color, position).add(*connection).build());
             // Abilities
             for (i, kind) in deck.iter().enumerate() {
                 ability::instantiate(world, e, i, *kind);
             }
+            let names = deck.map(|kind| kind.get().metadata().name.as_str());
+            log::info!("{connection:?} joined as {e:?} with {}.", names.join(", "));
             // TODO: reliable transport
-            socket.broadcast(&Packet::PlayerSpawn(e, *connection, *deck, color));
+            socket.broadcast(&Packet::PlayerSpawn(e, *connection, deck, color, position));
         }
         // Synchronize world state with every new connection
         for &connection in socket.connections() {
             // Existing players
-            for (e, (c, player)) in world.query_mut::<(&Connection, &Player)>() {
+            for (e, (c, player, transform)) in world.query_mut::<(&Connection, &Player, &Transform)>() {
                 // TODO: reliable transport
-                socket.send(connection, &Packet::PlayerSpawn(e, *c, player.deck, player.color));
+                socket.send(connection, &Packet::PlayerSpawn(e, *c, player.deck, player.color, transform.translation));
             }
         }
     }
     // Client spawns player whenever it's told so
     if cfg!(client) {
         for (connection, packet) in socket.packets() {
-            let Packet::PlayerSpawn(e, c, deck, color) = packet else {
+            let Packet::PlayerSpawn(e, c, deck, color, position) = packet else {
                 continue;
             };
             // Player:
-            world.spawn_at(*e, prefab(*deck, *color).build());
+            world.spawn_at(*e, prefab(*deck, *color, *position).build());
             // Health bar:
             world.spawn(health::gui_prefab(*e).build());
             // Abilities:
@@ -190,47 +212,248 @@ pub fn instantiate_spawn_indicator(world: &mut World, entity: Entity) {
     ));
 }
 
+// TODO: these will be calculated from player abilities
+const SPEED: f32 = 1700.0;
+const JUMP: f32 = 1500.0;
+const JUMP_GRACE_PERIOD: f32 = 0.1;
+const JUMP_TERM_VELOCITY: f32 = 500.0;
+const FRICTION: f32 = 5.0;
+const DROP_THROUGH_TIME: f32 = 0.25;
+/// Maximum fall speed while pressing into a wall.
+const WALL_SLIDE_SPEED: f32 = 250.0;
+/// Vertical velocity gained when jumping off a wall.
+const WALL_JUMP: f32 = 1300.0;
+/// Horizontal velocity gained away from the wall when jumping off it.
+const WALL_JUMP_KICK: f32 = 900.0;
+const GRAVITY: f32 = 2500.0;
+/// Frame time of the simulated [launch_arc].
+const ARC_DT: f32 = 1.0 / 60.0;
+/// Seconds of the [launch_arc] simulated, ie. how far it can fall.
+const ARC_TIME: f32 = 3.0;
+
+/// Positions, relative to the takeoff point, of a player making a running
+/// jump on flat ground: full speed, holding jump and a direction. Follows
+/// [platformer_controller] and physics frame by frame.
+pub fn jump_arc() -> impl Iterator<Item = Vec2<f32>> {
+    // Terminal velocity of running, where damping cancels out input
+    let run = SPEED / FRICTION;
+    launch_arc(vec2!(run, JUMP))
+}
+
+/// Positions, relative to where it started, of a player launched at
+/// `velocity`, ie. by a jump pad, while holding right.
+pub fn launch_arc(mut velocity: Vec2<f32>) -> impl Iterator<Item = Vec2<f32>> {
+    let mut position = vec2!(0.0, 0.0);
+    (0..(ARC_TIME / ARC_DT) as usize).map(move |_| {
+        velocity.x += SPEED * ARC_DT;
+        velocity /= 1.0 + FRICTION * ARC_DT;
+        velocity.y -= GRAVITY * ARC_DT;
+        position += velocity * ARC_DT;
+        position
+    })
+}
+
+/// Furthest horizontal distance a player can jump from a ledge while
+/// staying at least `height` above it, if it's within reach at all.
+/// Counts jumping late, during the grace period after running off.
+pub fn jump_reach(height: f32) -> Option<f32> {
+    let grace = SPEED / FRICTION * JUMP_GRACE_PERIOD;
+    jump_arc()
+        .filter(|p| p.y >= height)
+        .map(|p| p.x + grace)
+        .reduce(f32::max)
+}
+
+/// What a player's movement is like this frame, given its contacts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Movement {
+    /// On the ground, or just walked off a ledge. Can jump.
+    Grounded,
+    /// Falling while pressing into a wall on the `side` of `-1.0`(left)
+    /// or `1.0`(right). Can wall jump.
+    WallSlide { side: f32 },
+    /// In the air.
+    Airborne,
+}
+
+impl Movement {
+    /// Figure out the movement state from a player's contacts, velocity and
+    /// horizontal input.
+    fn new(grounded: &Grounded, walls: &Walls, velocity: Vec2<f32>, dx: f32) -> Self {
+        match grounded {
+            Grounded::Yes { .. } => return Self::Grounded,
+            // Allow short-while after falling off cliff...
+            // ...but not double jumping
+            Grounded::No { time } if *time <= JUMP_GRACE_PERIOD && velocity.y <= 0.0 => {
+                return Self::Grounded;
+            },
+            Grounded::No { .. } => {},
+        }
+        // Only slide down, not up
+        if velocity.y > 0.0 {
+            return Self::Airborne;
+        }
+        match (&walls.left, &walls.right) {
+            (Some(_), _) if dx < 0.0 => Self::WallSlide { side: -1.0 },
+            (_, Some(_)) if dx > 0.0 => Self::WallSlide { side: 1.0 },
+            _ => Self::Airborne,
+        }
+    }
+}
+
 /// System that updates player controllers.
 pub fn platformer_controller(world: &mut World, time: &Time) {
     /// Queries all players
     type Query<'a> = (
         &'a mut KinematicBody,
         &'a Grounded,
+        &'a Walls,
+        &'a Gravity,
+        &'a mut DropThrough,
         &'a Input,
         Option<&'a TimeScale>,
+        Option<&'a Boosts>,
     );
-    // TODO: these will be calculated from player abilities
-    const SPEED: f32 = 1700.0;
-    const JUMP: f32 = 1500.0;
-    const JUMP_GRACE_PERIOD: f32 = 0.1;
-    const JUMP_TERM_VELOCITY: f32 = 500.0;
-    const FRICTION: f32 = 5.0;
-
-    for (_, (kb, grounded, input, scale)) in world.query_mut::<Query>() {
+
+    for (_, (kb, grounded, walls, gravity, drop, input, scale, boosts)) in world.query_mut::<Query>() {
         let scale = scale
             .map(|s| s.0)
             .unwrap_or(1.0);
-        // Movement
-        kb.velocity.x +=  SPEED * input.dx() * time.dt() * scale;
+        let speed = SPEED * boosts
+            .map(Boosts::speed_factor)
+            .unwrap_or(1.0);
+        let movement = Movement::new(grounded, walls, kb.velocity, input.dx());
+        // Walk along the slope of the ground, if any
+        let (axis, material) = match grounded {
+            Grounded::Yes { surface, .. } => (
+                vec2!(surface.normal.y, -surface.normal.x),
+                surface.material,
+            ),
+            Grounded::No { .. } => (vec2!(1.0, 0.0), Material::default()),
+        };
+        // Movement, slippery surfaces are harder to get going on
+        let traction = material.friction.min(1.0);
+        kb.velocity += axis * speed * traction * input.dx() * time.dt() * scale;
+        // Don't slide down slopes
+        kb.velocity -= axis * axis.dot(&gravity.acceleration) * time.dt() * scale;
+        // Wall slide
+        if let Movement::WallSlide { .. } = movement {
+            kb.velocity.y = kb.velocity.y.max(-WALL_SLIDE_SPEED);
+        }
         // Jump
         if input.dy() > 0.0 {
-            let can_jump = match grounded {
-                Grounded::Yes { .. } => true,
-                Grounded::No { time } => {
-                    // Allow short-while after falling off cliff...
-                    *time <= JUMP_GRACE_PERIOD
-                    // ...but not double jumping
-                    && kb.velocity.y <= 0.0
+            match movement {
+                Movement::Grounded => {
+                    kb.velocity.y += JUMP;
                 },
-            };
-            if can_jump {
-                kb.velocity.y += JUMP;
+                Movement::WallSlide { side } => {
+                    kb.velocity = vec2!(-side * WALL_JUMP_KICK, WALL_JUMP);
+                },
+                Movement::Airborne => {},
             }
         // Jump termination
         } else if kb.velocity.y > JUMP_TERM_VELOCITY {
             kb.velocity.y = JUMP_TERM_VELOCITY;
         }
+        // Drop through one-way platforms
+        drop.time = (drop.time - time.dt() * scale).max(0.0);
+        if input.dy() < 0.0 && matches!(grounded, Grounded::Yes { .. }) {
+            drop.time = DROP_THROUGH_TIME;
+        }
         // Damping
-        kb.velocity /= 1.0 + FRICTION * time.dt() * scale;
+        kb.velocity /= 1.0 + FRICTION * material.friction * time.dt() * scale;
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::Surface;
+
+    fn surface(normal: Vec2<f32>) -> Surface {
+        Surface {
+            normal,
+            velocity: vec2!(0.0, 0.0),
+            material: Material::default(),
+        }
+    }
+
+    #[test]
+    fn grounded_when_touching_ground() {
+        let grounded = Grounded::Yes {
+            time: 1.0,
+            surface: surface(vec2!(0.0, 1.0)),
+        };
+        let walls = Walls {
+            left: Some(surface(vec2!(1.0, 0.0))),
+            right: None,
+        };
+        // Ground takes precedence over walls
+        let movement = Movement::new(&grounded, &walls, vec2!(0.0, 0.0), -1.0);
+        assert_eq!(movement, Movement::Grounded);
+    }
+
+    #[test]
+    fn grounded_during_grace_period() {
+        let walls = Walls::default();
+        let falling = vec2!(0.0, -10.0);
+
+        let grace = Grounded::No { time: JUMP_GRACE_PERIOD / 2.0 };
+        assert_eq!(Movement::new(&grace, &walls, falling, 0.0), Movement::Grounded);
+
+        let late = Grounded::No { time: JUMP_GRACE_PERIOD * 2.0 };
+        assert_eq!(Movement::new(&late, &walls, falling, 0.0), Movement::Airborne);
+        // No double jumping
+        let rising = vec2!(0.0, 10.0);
+        assert_eq!(Movement::new(&grace, &walls, rising, 0.0), Movement::Airborne);
+    }
+
+    #[test]
+    fn wall_slide_when_pressing_into_wall() {
+        let airborne = Grounded::No { time: 1.0 };
+        let falling = vec2!(0.0, -10.0);
+        let walls = Walls {
+            left: Some(surface(vec2!(1.0, 0.0))),
+            right: None,
+        };
+        assert_eq!(
+            Movement::new(&airborne, &walls, falling, -1.0),
+            Movement::WallSlide { side: -1.0 },
+        );
+        // Not pressing into it
+        assert_eq!(Movement::new(&airborne, &walls, falling, 0.0), Movement::Airborne);
+        assert_eq!(Movement::new(&airborne, &walls, falling, 1.0), Movement::Airborne);
+
+        let walls = Walls {
+            left: None,
+            right: Some(surface(vec2!(-1.0, 0.0))),
+        };
+        assert_eq!(
+            Movement::new(&airborne, &walls, falling, 1.0),
+            Movement::WallSlide { side: 1.0 },
+        );
+    }
+
+    #[test]
+    fn no_wall_slide_while_rising() {
+        let airborne = Grounded::No { time: 1.0 };
+        let walls = Walls {
+            left: None,
+            right: Some(surface(vec2!(-1.0, 0.0))),
+        };
+        // ie. right after a wall jump
+        let rising = vec2!(-WALL_JUMP_KICK, WALL_JUMP);
+        assert_eq!(Movement::new(&airborne, &walls, rising, 1.0), Movement::Airborne);
+    }
+
+    #[test]
+    fn jump_reach_shrinks_with_height() {
+        let apex = jump_arc().map(|p| p.y).reduce(f32::max).unwrap();
+        assert!(apex > 0.0);
+        assert_eq!(jump_reach(apex + 1.0), None);
+        let high = jump_reach(apex * 0.9).unwrap();
+        let level = jump_reach(0.0).unwrap();
+        let low = jump_reach(-200.0).unwrap();
+        assert!(high < level && level < low);
+    }
+}
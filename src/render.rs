@@ -4,8 +4,9 @@ use crate::{
     platform::{Canvas, Socket},
     transform::{Transform, Parent},
     math::{ Vec2, vec2 },
-    ability::{Ability, BubbleShield, Cooldown},
-    health::Health, physics, network::Packet
+    ability::Ability,
+    health::{ Health, DamageSource, Environment }, physics::{ self, Collider }, network::Packet,
+    timescale::TimeScale, pickup::PickupKind, player::Player,
 };
 
 /// A type of [Sprite]
@@ -14,6 +15,8 @@ use crate::{
 /// This type is passed directly to `platform/`.
 /// For Typescript binding simplicity, every field should be
 /// aligned to 4 bytes(ie. `u32`, `f32`).
+/// Variants are told apart by their tag, so they must stay in the same
+/// order as `Costume` in `platform/mod.ts`.
 #[derive(Debug, Clone)]
 #[repr(u32)]
 pub enum Costume {
@@ -48,6 +51,8 @@ pub enum Costume {
     },
     Push {
         position: Vec2<f32>,
+        /// How far the push reaches
+        radius: f32,
     },
     Freeze,
     Lightning {
@@ -72,6 +77,61 @@ pub enum Costume {
         position: Vec2<f32>,
         width: f32,
     },
+    /// Arbitrary polygons, see [Sprite::outline]
+    Shape {
+        position: Vec2<f32>,
+        rotation: f32,
+    },
+    /// Area hurting players, centered on `position`
+    Hazard {
+        position: Vec2<f32>,
+        size: Vec2<f32>,
+    },
+    JumpPad {
+        position: Vec2<f32>,
+        width: f32,
+    },
+    Teleporter {
+        position: Vec2<f32>,
+    },
+    /// Effect of a jump pad launching a player
+    Launch {
+        position: Vec2<f32>,
+    },
+    /// Effect of a teleporter, at both ends
+    Warp {
+        position: Vec2<f32>,
+    },
+    /// Pieces of a broken platform
+    Debris {
+        position: Vec2<f32>,
+        width: f32,
+    },
+    Pickup {
+        position: Vec2<f32>,
+        kind: PickupKind,
+    },
+    Sniper {
+        position: Vec2<f32>,
+        rotation: f32,
+        /// Length of the aiming sight, grows while charging
+        sight: f32,
+    },
+    /// Trail of a hitscan shot
+    Tracer {
+        from: Vec2<f32>,
+        to: Vec2<f32>,
+    },
+    /// Grappling hook's rope
+    Rope {
+        from: Vec2<f32>,
+        to: Vec2<f32>,
+    },
+    /// Aura around a boosted player
+    Boost {
+        position: Vec2<f32>,
+        kind: PickupKind,
+    },
 }
 
 /// Whether a [Sprite] is visible or not.
@@ -94,6 +154,9 @@ pub struct Sprite {
     pub visibility: Visibility,
     /// Handle of the `platform`'s object(for drop management).
     pub handle: Option<u32>,
+    /// Polygons making up [Costume::Shape]s, relative to their position.
+    /// Like the costume, it cannot be changed after initialization.
+    pub outline: Vec<Vec<Vec2<f32>>>,
 }
 
 impl Sprite {
@@ -103,8 +166,19 @@ impl Sprite {
             costume,
             visibility: Default::default(),
             handle: None,
+            outline: Vec::new(),
         }
     }
+
+    /// Create a new [Costume::Shape] sprite matching a collider
+    pub fn outline(collider: &Collider, transform: &Transform) -> Self {
+        let mut sprite = Self::new(Costume::Shape {
+            position: transform.translation,
+            rotation: transform.rotation,
+        });
+        sprite.outline = collider.outline();
+        sprite
+    }
 }
 
 impl Drop for Sprite {
@@ -148,10 +222,15 @@ pub fn animate_player_sprites(world: &mut World) {
     if cfg!(server) {
         return;
     }
-    for (_, (transform, sprite)) in world.query_mut::<(&Transform, &mut Sprite)>() {
+    for (_, (transform, sprite, dilation)) in world.query_mut::<(&Transform, &mut Sprite, Option<&TimeScale>)>() {
         let Costume::Player { position, scale, lean, .. } = &mut sprite.costume else {
             continue;
         };
+        // Squash/stretch and lean in slow-motion too
+        let rate = dilation
+            .map(|s| s.0)
+            .unwrap_or(1.0)
+            .min(1.0);
         let target = transform.translation;
         let delta = target - *position;
 
@@ -165,10 +244,10 @@ pub fn animate_player_sprites(world: &mut World) {
         
         *position += delta * 0.6;
         // Lean in direction of movement unless jumping/falling
-        *lean += (target_lean - *lean) * 0.2;
+        *lean += (target_lean - *lean) * 0.2 * rate;
         // Squash/stretch
-        scale.x += (target_scale.x - scale.x) * 0.6;
-        scale.y += (target_scale.y - scale.y) * 0.6;
+        scale.x += (target_scale.x - scale.x) * 0.6 * rate;
+        scale.y += (target_scale.y - scale.y) * 0.6 * rate;
 
         // Snap back
         if delta.norm_squared() > 10000.0 {
@@ -207,6 +286,7 @@ pub fn animate_handheld_sprites(world: &mut World) {
             Costume::AssaultRifle { position, rotation } => (position, rotation),
             Costume::DualGun { position, rotation } => (position, rotation),
             Costume::Shield { position, rotation } => (position, rotation),
+            Costume::Sniper { position, rotation, .. } => (position, rotation),
             _ => {
                 continue;
             }
@@ -226,14 +306,12 @@ pub fn animate_handheld_sprites(world: &mut World) {
     }
 }
 
-pub fn animate_bubble_shield_sprite(world: &mut World) {
+pub fn animate_health_bar_sprites(world: &mut World) {
     if cfg!(server) {
         return;
     }
-    for (_, (transform, ability, shield, cooldown, sprite)) in world.query_mut::<(
-        &Transform, &Ability, &BubbleShield, &Cooldown, &mut Sprite
-    )>() {
-        let Costume::BubbleShield { position, radius } = &mut sprite.costume else {
+    for (_, (transform, parent, sprite)) in &mut world.query::<(&Transform, &Parent, &mut Sprite)>() {
+        let Costume::HealthBar { position, percentage } = &mut sprite.costume else {
             continue;
         };
         let target = transform.translation;
@@ -241,33 +319,37 @@ pub fn animate_bubble_shield_sprite(world: &mut World) {
 
         // Damp position
         *position += 0.9 * delta;
-        // Radius is exact
-        *radius = shield.radius.max(0.0);
-        // Visibility
-        sprite.visibility = match ability.active && cooldown.0 <= 0.0 {
-            true => Visibility::Shown,
-            false => Visibility::Hidden,
-        };
-    }   
+        // Percentage
+        if let Ok(health) = world.get::<&Health>(parent.0) {
+            *percentage = health.now / health.max;
+        }
+    }
 }
 
-pub fn animate_health_bar_sprites(world: &mut World) {
+/// System that moves platform sprites along with their [Transform]
+pub fn animate_platform_sprites(world: &mut World) {
     if cfg!(server) {
         return;
     }
-    for (_, (transform, parent, sprite)) in &mut world.query::<(&Transform, &Parent, &mut Sprite)>() {
-        let Costume::HealthBar { position, percentage } = &mut sprite.costume else {
+    for (_, (transform, sprite)) in world.query_mut::<(&Transform, &mut Sprite)>() {
+        let Costume::Platform { position, .. } = &mut sprite.costume else {
             continue;
         };
-        let target = transform.translation;
-        let delta = target - *position;
+        *position = transform.translation;
+    }
+}
 
-        // Damp position
-        *position += 0.9 * delta;
-        // Percentage
-        if let Ok(health) = world.get::<&Health>(parent.0) {
-            *percentage = health.now / health.max;
-        }
+/// System that moves shape sprites along with their [Transform]
+pub fn animate_shape_sprites(world: &mut World) {
+    if cfg!(server) {
+        return;
+    }
+    for (_, (transform, sprite)) in world.query_mut::<(&Transform, &mut Sprite)>() {
+        let Costume::Shape { position, rotation } = &mut sprite.costume else {
+            continue;
+        };
+        *position = transform.translation;
+        *rotation = transform.rotation;
     }
 }
 
@@ -292,4 +374,35 @@ pub fn draw_cooldowns(socket: &Socket, canvas: &Canvas) {
         };
         canvas.set_cooldown(*binding, *duration);
     }
-}
\ No newline at end of file
+}
+
+/// System that adds kills to the kill feed
+pub fn draw_kill_feed(world: &mut World, socket: &Socket, canvas: &Canvas) {
+    if cfg!(server) {
+        return;
+    }
+    let color = |e| world
+        .get::<&Player>(e)
+        .map(|player| player.color())
+        .ok();
+    for (_, packet) in socket.packets() {
+        let Packet::PlayerKilled { victim, source } = *packet else {
+            continue;
+        };
+        let Some(victim) = color(victim) else {
+            continue;
+        };
+        match source {
+            DamageSource::Player(killer) => canvas.add_kill(victim, color(killer), ""),
+            DamageSource::Environment(environment) => {
+                let cause = match environment {
+                    Environment::KillZone => "fell",
+                    Environment::DamageZone => "was hurt by a hazard",
+                    Environment::Crusher => "was crushed",
+                    Environment::OutOfBounds => "left the level",
+                };
+                canvas.add_kill(victim, None, cause);
+            },
+        }
+    }
+}
@@ -35,11 +35,8 @@ export async function findOrCreateUser(gid: string) {
         name: "player1234",
         gid,
         // TODO: Until the collection aspect of the game is implemented,
-        // every single ability is unlocked(yay!)
+        // every single ability is unlocked(yay!), once it has an icon
         deck: ["shotgun", "push", "time-freeze", "dual-gun"],
-        unlocked: Object.keys(abilities).filter((n) => ![
-            // not yet implemented
-            "sniper", "grappling"
-        ].includes(n)),
+        unlocked: Object.keys(abilities).filter((id) => abilities[id].icon),
     }).save();
 }
\ No newline at end of file
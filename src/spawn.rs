@@ -0,0 +1,141 @@
+//! Choosing where players (re)spawn, away from enemies and from
+//! places where players keep dying.
+
+use hecs::{ Entity, With, Without, World };
+
+use crate::{
+    math::{ Vec2, vec2 },
+    transform::Transform,
+    player::Player,
+    health::Dead,
+    platform::Time,
+};
+
+/// Distance to the nearest enemy past which a spawn point is
+/// considered safe.
+const SAFE_DISTANCE: f32 = 1000.0;
+/// Radius around a death in which spawn points are penalized.
+const DEATH_RADIUS: f32 = 400.0;
+/// Score lost by a spawn point for a fresh death next to it.
+const DEATH_PENALTY: f32 = 500.0;
+/// Seconds a death is remembered for.
+const DEATH_MEMORY: f32 = 20.0;
+/// Random score added to break ties between equally good points.
+const JITTER: f32 = 50.0;
+/// Used when the level has no spawn points at all.
+const FALLBACK: Vec2<f32> = vec2!(100.0, 500.0);
+
+/// Component for places players can (re)spawn at.
+#[derive(Debug, Default, Clone)]
+pub struct SpawnPoint;
+
+/// Component for a recent death, discouraging spawns nearby.
+#[derive(Debug, Clone)]
+struct Grave {
+    position: Vec2<f32>,
+    /// Seconds left before it's forgotten.
+    time_left: f32,
+}
+
+/// Remember that a player died at `position`.
+pub fn record_death(world: &mut World, position: Vec2<f32>) {
+    world.spawn((
+        Grave {
+            position,
+            time_left: DEATH_MEMORY,
+        },
+    ));
+}
+
+/// Forget every death, ie. when the level changes.
+pub fn forget_all_deaths(world: &mut World) {
+    let graves = world
+        .query_mut::<&Grave>()
+        .into_iter()
+        .map(|(e, _)| e)
+        .collect::<Vec<_>>();
+    for e in graves {
+        world.despawn(e).unwrap();
+    }
+}
+
+/// Find the best place to spawn `player`, ie. the spawn point furthest
+/// from living enemies with the fewest recent deaths around it.
+pub fn select(world: &World, player: Entity) -> Vec2<f32> {
+    let enemies = world
+        .query::<Without<With<&Transform, &Player>, &Dead>>()
+        .iter()
+        .filter(|(e, _)| *e != player)
+        .map(|(_, transform)| transform.translation)
+        .collect::<Vec<_>>();
+    let graves = world
+        .query::<&Grave>()
+        .iter()
+        .map(|(_, grave)| grave.clone())
+        .collect::<Vec<_>>();
+
+    world
+        .query::<With<&Transform, &SpawnPoint>>()
+        .iter()
+        .map(|(_, transform)| {
+            let p = transform.translation;
+            (p, score(p, &enemies, &graves) + JITTER * fastrand::f32())
+        })
+        .max_by(|(_, x), (_, y)| x.total_cmp(y))
+        .map_or(FALLBACK, |(p, _)| p)
+}
+
+/// How good of a spawn `p` is, higher is better.
+fn score(p: Vec2<f32>, enemies: &[Vec2<f32>], graves: &[Grave]) -> f32 {
+    let safety = enemies
+        .iter()
+        .map(|e| (e - p).norm())
+        .fold(SAFE_DISTANCE, f32::min);
+    let danger = graves
+        .iter()
+        .filter(|grave| (grave.position - p).norm() < DEATH_RADIUS)
+        .map(|grave| DEATH_PENALTY * grave.time_left / DEATH_MEMORY)
+        .sum::<f32>();
+    safety - danger
+}
+
+/// System that forgets old deaths.
+pub fn forget_deaths(world: &mut World, time: &Time) {
+    if cfg!(client) {
+        return;
+    }
+    let mut forgotten = Vec::new();
+    for (e, grave) in world.query_mut::<&mut Grave>() {
+        grave.time_left -= time.dt();
+        if grave.time_left <= 0.0 {
+            forgotten.push(e);
+        }
+    }
+    for e in forgotten {
+        world.despawn(e).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefers_points_away_from_enemies() {
+        let enemies = [vec2!(0.0, 0.0)];
+        assert!(score(vec2!(800.0, 0.0), &enemies, &[]) > score(vec2!(100.0, 0.0), &enemies, &[]));
+        // Far enough is as good as it gets
+        assert_eq!(score(vec2!(2000.0, 0.0), &enemies, &[]), score(vec2!(3000.0, 0.0), &enemies, &[]));
+    }
+
+    #[test]
+    fn avoids_recent_deaths() {
+        let fresh = Grave { position: vec2!(0.0, 0.0), time_left: DEATH_MEMORY };
+        let old = Grave { position: vec2!(0.0, 0.0), time_left: 1.0 };
+        let p = vec2!(100.0, 0.0);
+        assert!(score(p, &[], &[fresh.clone()]) < score(p, &[], &[old]));
+        assert!(score(p, &[], &[fresh.clone()]) < score(p, &[], &[]));
+        // Too far away to matter
+        assert_eq!(score(vec2!(1000.0, 0.0), &[], &[fresh]), score(p, &[], &[]));
+    }
+}
@@ -0,0 +1,128 @@
+use hecs::{ Entity, World };
+
+use crate::{
+    platform::{ Socket, Time },
+    network::Packet,
+    ability::Ability,
+};
+
+/// Seconds between resending active modifiers to clients.
+const RESEND_INTERVAL: f32 = 1.0;
+
+/// Component for an entity's invidual time scale, computed from every
+/// [TimeModifier] affecting it. Entities without one aren't dilated.
+#[derive(Debug, Clone, Copy)]
+pub struct TimeScale(pub f32);
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Component for a source of time dilation. Each lives on its own
+/// entity so that they stack, and expires in real time.
+#[derive(Debug, Clone, Copy)]
+pub struct TimeModifier {
+    /// Entity responsible for it, ie. the player who cast it.
+    pub source: Entity,
+    /// Multiplier on the passage of time.
+    pub factor: f32,
+    /// Seconds left(unaffected by dilation) before it expires.
+    pub time_left: f32,
+    /// Who's affected.
+    pub target: TimeTarget,
+}
+
+/// Entities affected by a [TimeModifier]. Abilities are affected along
+/// with their owner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeTarget {
+    /// Every entity except this one.
+    AllExcept(Entity),
+}
+
+impl TimeTarget {
+    /// Is `entity` affected?
+    pub fn affects(&self, entity: Entity) -> bool {
+        match self {
+            TimeTarget::AllExcept(e) => *e != entity,
+        }
+    }
+}
+
+/// Start dilating time, locally and on clients. Modifiers from different
+/// sources stack, but re-applying one from the same source and target
+/// only refreshes it.
+pub fn dilate(world: &mut World, socket: &Socket, modifier: TimeModifier) {
+    socket.broadcast(&Packet::TimeModifier(modifier));
+    add(world, modifier);
+}
+
+/// See [dilate]
+fn add(world: &mut World, modifier: TimeModifier) {
+    let existing = world
+        .query_mut::<&mut TimeModifier>()
+        .into_iter()
+        .find(|(_, m)| m.source == modifier.source && m.target == modifier.target);
+    match existing {
+        Some((_, m)) => *m = modifier,
+        None => {
+            world.spawn((modifier,));
+        },
+    }
+}
+
+/// System that expires [TimeModifier]s and computes every entity's
+/// resulting [TimeScale].
+pub fn update(world: &mut World, socket: &Socket, time: &Time) {
+    // Client mirrors the server's modifiers
+    if cfg!(client) {
+        for (_, packet) in socket.packets() {
+            let Packet::TimeModifier(modifier) = packet else {
+                continue;
+            };
+            add(world, *modifier);
+        }
+    }
+    // Expire
+    let mut expired = Vec::new();
+    let mut active = Vec::new();
+    for (e, modifier) in world.query_mut::<&mut TimeModifier>() {
+        let before = modifier.time_left;
+        modifier.time_left -= time.dt();
+        if modifier.time_left <= 0.0 {
+            expired.push(e);
+            continue;
+        }
+        active.push(*modifier);
+        // TODO: reliable transport
+        let resend = (before / RESEND_INTERVAL).ceil() != (modifier.time_left / RESEND_INTERVAL).ceil();
+        if cfg!(server) && resend {
+            socket.broadcast(&Packet::TimeModifier(*modifier));
+        }
+    }
+    // Newcomers joining mid-effect
+    if cfg!(server) {
+        for &connection in socket.connections() {
+            for modifier in &active {
+                socket.send(connection, &Packet::TimeModifier(*modifier));
+            }
+        }
+    }
+    for e in expired {
+        world.despawn(e).unwrap();
+    }
+    // Stack
+    for (e, scale) in &mut world.query::<&mut TimeScale>() {
+        let target = world
+            .get::<&Ability>(e)
+            .map(|ability| ability.owner)
+            .unwrap_or(e);
+        scale.0 = active
+            .iter()
+            .filter(|modifier| modifier.target.affects(target))
+            .map(|modifier| modifier.factor)
+            .product();
+    }
+}
@@ -11,11 +11,12 @@ export function Game({ deck, playing }) {
     const ref = useRef();
     const instance = useMemo(() => game(port), []);
     const [cooldowns, setCooldowns] = useState([0, 0, 0, 0]);
+    const [kills, setKills] = useState([]);
 
     // Attach the instance of the game to DOM:
     useEffect(() => {
         instance.then(({ hook }) => {
-            hook(ref.current, setCooldowns);
+            hook(ref.current, setCooldowns, setKills);
         });
     }, [ref]);
     // Send channel ID to server
@@ -29,6 +30,16 @@ export function Game({ deck, playing }) {
 
     return (
         <div ref={ref} class="w:100vw h:100vh">
+            <div class="kill-feed column">
+                {kills.map(({ id, victim, killer, cause }) => (
+                    <div class="kill row" key={id}>
+                        {killer && <span class="player" style={{ background: killer }}/>}
+                        {killer && <span>killed</span>}
+                        <span class="player" style={{ background: victim }}/>
+                        {cause && <span>{cause}</span>}
+                    </div>
+                ))}
+            </div>
             <div class="ability-bar row">
                 {deck.map((ability, i) => (
                     <AbilityIcon id={ability} binding={i + 1} key={i} cooldown={cooldowns[i]}/>    